@@ -1,16 +1,24 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, token, Address, BytesN, Env,
-    Symbol, Vec, vec, Map, String, panic_with_error, log, 
-    xdr::{ScErrorType, ScErrorCode},
+    contract, contractimpl, log, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec,
+    vec, Map, String,
 };
 
-mod types;
+pub mod types;
 use types::*;
 
+mod test;
+
 #[contract]
 pub struct NotaryContract;
 
+/// The lists a stored document appears in, captured when it is loaded for an update
+struct Listing {
+    signers: Vec<Address>,
+    awaiting: Vec<Address>,
+    status: DocumentStatus,
+}
+
 #[contractimpl]
 impl NotaryContract {
     /// Initialize the contract
@@ -19,270 +27,2057 @@ impl NotaryContract {
             return Err(NotaryError::AlreadyExists);
         }
 
-        let state = NotaryState {
-            admin: admin.clone(),
-            documents: Map::new(&env),
-            user_documents: Map::new(&env),
-            authorities: Vec::new(&env),
-            claims: Map::new(&env),
-            settings: Map::new(&env),
-        };
-
-        env.storage().instance().set(&STATE, &state);
         env.storage().instance().set(&ADMIN, &admin);
+        Self::bump_instance(&env);
+
+        Ok(())
+    }
+
+    /// Offer the admin role to `new_admin`, replacing any earlier offer. The role moves
+    /// only once `new_admin` calls `accept_admin`.
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) -> Result<(), NotaryError> {
+        admin.require_auth();
+
+        if admin != Self::get_admin(&env)? {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        env.storage().instance().set(&PEND_ADM, &new_admin);
+        Self::bump_instance(&env);
+
+        env.events().publish((ADMIN,), NotaryEvent::AdminProposed(admin, new_admin));
+
+        Ok(())
+    }
+
+    /// Withdraw a pending admin offer
+    pub fn cancel_admin_proposal(env: Env, admin: Address) -> Result<(), NotaryError> {
+        admin.require_auth();
+
+        if admin != Self::get_admin(&env)? {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        let pending: Address = env.storage().instance().get(&PEND_ADM)
+            .ok_or(NotaryError::NotFound)?;
+        env.storage().instance().remove(&PEND_ADM);
+
+        env.events().publish((ADMIN,), NotaryEvent::AdminProposalCancelled(pending));
+
+        Ok(())
+    }
+
+    /// Take over the admin role as the proposed admin
+    pub fn accept_admin(env: Env) -> Result<(), NotaryError> {
+        let new_admin: Address = env.storage().instance().get(&PEND_ADM)
+            .ok_or(NotaryError::NotFound)?;
+        new_admin.require_auth();
+
+        let old_admin = Self::get_admin(&env)?;
+        env.storage().instance().set(&ADMIN, &new_admin);
+        env.storage().instance().remove(&PEND_ADM);
+        Self::bump_instance(&env);
+
+        env.events().publish((ADMIN,), NotaryEvent::AdminChanged(old_admin, new_admin));
+
+        Ok(())
+    }
+
+    /// Get the current admin
+    pub fn get_admin_address(env: Env) -> Result<Address, NotaryError> {
+        Self::get_admin(&env)
+    }
+
+    /// Get the address an admin offer is waiting on, if any
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&PEND_ADM)
+    }
+
+    /// Give `account` a role
+    pub fn grant_role(env: Env, admin: Address, role: Role, account: Address) -> Result<(), NotaryError> {
+        admin.require_auth();
+
+        if admin != Self::get_admin(&env)? {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        let key = DataKey::Role(role.clone(), account.clone());
+        if !env.storage().persistent().has(&key) {
+            env.storage().persistent().set(&key, &true);
+            Self::bump(&env, &key);
+            env.events().publish((ROLES,), NotaryEvent::RoleGranted(role, account));
+        }
+
+        Ok(())
+    }
+
+    /// Take a role away from `account`
+    pub fn revoke_role(env: Env, admin: Address, role: Role, account: Address) -> Result<(), NotaryError> {
+        admin.require_auth();
+
+        if admin != Self::get_admin(&env)? {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        let key = DataKey::Role(role.clone(), account.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(NotaryError::NotFound);
+        }
+        env.storage().persistent().remove(&key);
+
+        env.events().publish((ROLES,), NotaryEvent::RoleRevoked(role, account));
+
+        Ok(())
+    }
+
+    /// Check whether `account` holds a role, directly or as the admin
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        Self::require_role(&env, &account, role).is_ok()
+    }
+
+    /// Stop every change to documents and identity claims until `unpause` is called
+    pub fn pause(env: Env, caller: Address) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::Pauser)?;
+
+        env.storage().instance().set(&PAUSED, &true);
+        Self::bump_instance(&env);
+
+        env.events().publish((OPS,), NotaryEvent::Paused(caller));
+
+        Ok(())
+    }
+
+    /// Resume document activity after `pause`
+    pub fn unpause(env: Env, caller: Address) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::Pauser)?;
+
+        env.storage().instance().remove(&PAUSED);
+
+        env.events().publish((OPS,), NotaryEvent::Unpaused(caller));
+
+        Ok(())
+    }
+
+    /// Replace the contract code with previously uploaded wasm
+    pub fn upgrade(env: Env, caller: Address, wasm_hash: BytesN<32>) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::Upgrader)?;
+
+        env.deployer().update_current_contract_wasm(wasm_hash.clone());
+
+        env.events().publish((OPS,), NotaryEvent::Upgraded(wasm_hash));
 
         Ok(())
     }
 
-    /// Create a new document
+    /// Create a new document owned by `owner`, approved once `threshold` signers
+    /// have signed (all of them when `None`). In `ordered` mode signers must sign
+    /// in the order they are listed.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_document(
         env: Env,
+        owner: Address,
         hash: BytesN<32>,
         title: String,
         signers: Vec<Address>,
         metadata: Map<Symbol, String>,
+        threshold: Option<u32>,
+        ordered: bool,
     ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
 
-        if state.documents.contains_key(hash.clone()) {
+        if env.storage().persistent().has(&DataKey::Document(hash.clone())) {
             return Err(NotaryError::AlreadyExists);
         }
 
+        Self::validate_signers(&env, &signers)?;
+        let threshold = threshold.unwrap_or(signers.len());
+        Self::validate_threshold(&env, threshold, signers.len())?;
+        let expires_at = Self::compute_expiry(&env, &metadata)?;
+        Self::validate_claim_requirement(&env, &metadata)?;
+        Self::charge_fee(&env, &owner)?;
+
         // Create initial version with zero-filled parent hash
         let version = DocumentVersion {
             hash: hash.clone(),
             parent_hash: BytesN::from_array(&env, &[0; 32]), // Zero-filled bytes for no parent
             title: title.clone(),
             status: VersionStatus::PendingApproval,
-            creator: env.current_contract_address(),
+            creator: owner.clone(),
             created_at: env.ledger().timestamp(),
             updated_at: env.ledger().timestamp(),
             signatures: Vec::new(&env),
             required_signers: signers.clone(),
+            threshold,
+            signer_weights: Map::new(&env),
+            required_weight: 0,
+            ordered,
             metadata: metadata.clone(),
         };
 
         let document = Document {
             hash: hash.clone(),
             status: DocumentStatus::Pending,
-            owner: env.current_contract_address(),
+            owner: owner.clone(),
             created_at: env.ledger().timestamp(),
             updated_at: env.ledger().timestamp(),
-            current_version: 0,
+            current_version: None,
+            latest_version: 0,
+            expires_at,
             versions: vec![&env, version],
             authorized_signers: signers,
+            threshold,
+            signer_weights: Map::new(&env),
+            required_weight: 0,
+            ordered,
             metadata,
         };
 
-        state.documents.set(hash.clone(), document);
+        Self::index_version(&env, &hash, &hash, 0)?;
+        Self::save_document(&env, &document, None);
 
-        let mut user_docs = state.user_documents.get(env.current_contract_address())
-            .unwrap_or(Vec::new(&env));
-        user_docs.push_back(hash.clone());
-        state.user_documents.set(env.current_contract_address(), user_docs);
+        Self::index_push(&env, DocIndex::Owner(owner), &hash);
 
-        env.storage().instance().set(&STATE, &state);
         env.events().publish((DOCS,), NotaryEvent::DocumentCreated(hash));
 
         Ok(())
     }
 
     /// Helper: Check if address is authorized for document
-    fn is_authorized(document: Document, address: Address) -> bool {
-        address == document.owner || document.authorized_signers.contains(&address)
+    fn is_authorized(document: &Document, address: &Address) -> bool {
+        *address == document.owner || document.authorized_signers.contains(address)
     }
 
-    /// Add new version to document
-    pub fn add_version(
-        env: Env,
-        document_hash: BytesN<32>,
-        version_hash: BytesN<32>,
-        title: String,
-        metadata: Map<Symbol, String>,
+    /// Helper: Check a signer list against the MIN_SIGN/MAX_SIGN bounds and for duplicates
+    fn validate_signers(env: &Env, signers: &Vec<Address>) -> Result<(), NotaryError> {
+        let config = Self::load_config(env);
+
+        if signers.len() < config.min_signers {
+            return Err(NotaryError::TooFewSigners);
+        }
+
+        if signers.len() > config.max_signers {
+            return Err(NotaryError::TooManySigners);
+        }
+
+        let mut seen: Map<Address, bool> = Map::new(env);
+        for signer in signers.iter() {
+            if seen.contains_key(signer.clone()) {
+                return Err(NotaryError::DuplicateSigner);
+            }
+            seen.set(signer, true);
+        }
+
+        Ok(())
+    }
+
+    /// Helper: Check an approval threshold lies within 1..=signer count
+    fn validate_threshold(env: &Env, threshold: u32, signer_count: u32) -> Result<(), NotaryError> {
+        if threshold == 0 || threshold > signer_count {
+            log!(env, "threshold out of range", threshold, signer_count);
+            return Err(NotaryError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// Helper: Check weights cover only listed signers and the required weight is reachable.
+    /// An empty weight map with a zero required weight turns weighting off.
+    fn validate_weights(
+        env: &Env,
+        signers: &Vec<Address>,
+        weights: &Map<Address, u32>,
+        required_weight: u32,
     ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+        if required_weight == 0 {
+            if !weights.is_empty() {
+                log!(env, "weights given without a required weight");
+                return Err(NotaryError::InvalidInput);
+            }
+            return Ok(());
+        }
 
-        let mut document = state.documents.get(document_hash.clone())
-            .ok_or(NotaryError::NotFound)?;
+        for (signer, weight) in weights.iter() {
+            if weight == 0 || !signers.contains(&signer) {
+                log!(env, "invalid signer weight", signer, weight);
+                return Err(NotaryError::InvalidInput);
+            }
+        }
 
-        if !Self::is_authorized(document.clone(), env.current_contract_address()) {
-            return Err(NotaryError::Unauthorized);
+        let total = signers.iter()
+            .fold(0u64, |acc, signer| acc + weights.get(signer).unwrap_or(1) as u64);
+        if required_weight as u64 > total {
+            log!(env, "required weight unreachable", required_weight, total);
+            return Err(NotaryError::InvalidInput);
         }
 
-        let version = DocumentVersion {
-            hash: version_hash.clone(),
-            parent_hash: document_hash.clone(),
-            title,
-            status: VersionStatus::Draft,
-            creator: env.current_contract_address(),
-            created_at: env.ledger().timestamp(),
-            updated_at: env.ledger().timestamp(),
-            signatures: Vec::new(&env),
-            required_signers: document.authorized_signers.clone(),
-            metadata,
-        };
+        Ok(())
+    }
 
-        document.versions.push_back(version);
-        document.current_version = (document.versions.len() - 1) as u32;
-        document.updated_at = env.ledger().timestamp();
+    /// Helper: Whether a version's signatures satisfy its weight or, if unweighted, its threshold
+    fn is_approved(version: &DocumentVersion) -> bool {
+        if version.required_weight == 0 {
+            return version.signatures.len() >= version.threshold;
+        }
 
-        state.documents.set(document_hash.clone(), document);
-        env.storage().instance().set(&STATE, &state);
+        let weight = version.signatures.iter()
+            .fold(0u64, |acc, s| acc + version.signer_weights.get(s.signer).unwrap_or(1) as u64);
+        weight >= version.required_weight as u64
+    }
 
-        env.events().publish((DOCS,), NotaryEvent::VersionAdded(version_hash));
+    /// Helper: First listed signer of an ordered version who has not signed yet
+    fn next_signer(version: &DocumentVersion) -> Option<Address> {
+        version.required_signers.iter()
+            .find(|signer| !version.signatures.iter().any(|s| s.signer == *signer))
+    }
+
+    /// Helper: Mark every version approved before `approved` as superseded and make
+    /// `approved` the document's current version
+    fn supersede_versions(document: &mut Document, approved: u32) {
+        for index in 0..approved {
+            let mut version = document.versions.get(index).unwrap();
+            if version.status == VersionStatus::Approved {
+                version.status = VersionStatus::Superseded;
+                document.versions.set(index, version);
+            }
+        }
+        document.current_version = Some(approved);
+    }
+
+    /// Helper: The latest version while its signing rules may still change, `None` once it
+    /// is approved. Fails while signatures are being collected.
+    fn unsigned_latest_version(document: &Document) -> Result<Option<DocumentVersion>, NotaryError> {
+        let version = document.versions.get(document.latest_version).unwrap();
+        if version.status == VersionStatus::Approved {
+            return Ok(None);
+        }
+        if !version.signatures.is_empty() {
+            return Err(NotaryError::InvalidState);
+        }
+        Ok(Some(version))
+    }
+
+    /// Helper: Fail unless `caller` is the admin or has been granted `role`
+    fn require_role(env: &Env, caller: &Address, role: Role) -> Result<(), NotaryError> {
+        if *caller == Self::get_admin(env)? {
+            return Ok(());
+        }
+        let key = DataKey::Role(role, caller.clone());
+        if env.storage().persistent().has(&key) {
+            Self::bump(env, &key);
+            return Ok(());
+        }
+        Err(NotaryError::Unauthorized)
+    }
 
+    /// Helper: Fail while document and claim changes are paused
+    fn require_not_paused(env: &Env) -> Result<(), NotaryError> {
+        if env.storage().instance().get(&PAUSED).unwrap_or(false) {
+            return Err(NotaryError::InvalidState);
+        }
         Ok(())
     }
 
-    /// Sign a document version
-    pub fn sign_document(
-        env: Env,
-        document_hash: BytesN<32>,
-        signature: Signature,
-    ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+    /// Helper: Read the stored admin address
+    fn get_admin(env: &Env) -> Result<Address, NotaryError> {
+        env.storage().instance().get(&ADMIN)
+            .ok_or(NotaryError::InvalidState)
+    }
+
+    /// Helper: Load a document from its own storage entry, bumping its TTL
+    fn load_document(env: &Env, hash: &BytesN<32>) -> Result<Document, NotaryError> {
+        let mut document = Self::load_stored_document(env, hash)?;
+        Self::apply_expiry(env, &mut document);
+        Ok(document)
+    }
 
-        let mut document = state.documents.get(document_hash.clone())
+    /// Helper: Load a document that is about to be changed, along with the lists it is
+    /// stored under so `save_document` can move it without reading it back
+    fn load_document_for_update(env: &Env, hash: &BytesN<32>) -> Result<(Document, Listing), NotaryError> {
+        let mut document = Self::load_stored_document(env, hash)?;
+        let listing = Listing {
+            signers: document.authorized_signers.clone(),
+            awaiting: Self::awaiting_signers(env, &document),
+            status: document.status.clone(),
+        };
+        Self::apply_expiry(env, &mut document);
+        Ok((document, listing))
+    }
+
+    /// Helper: Read a document exactly as stored, bumping its TTL
+    fn load_stored_document(env: &Env, hash: &BytesN<32>) -> Result<Document, NotaryError> {
+        let key = DataKey::Document(hash.clone());
+        let document = env.storage().persistent().get(&key)
             .ok_or(NotaryError::NotFound)?;
+        Self::bump(env, &key);
+        Ok(document)
+    }
 
-        if !document.authorized_signers.contains(&env.current_contract_address()) {
-            return Err(NotaryError::Unauthorized);
+    /// Helper: Report a pending or active document whose expiry has passed as expired
+    fn apply_expiry(env: &Env, document: &mut Document) {
+        if Self::is_expired(env, document)
+            && matches!(document.status, DocumentStatus::Pending | DocumentStatus::Active)
+        {
+            document.status = DocumentStatus::Expired;
+        }
+    }
+
+    /// Helper: Fail for revoked or expired documents, which take no new versions or signatures
+    fn require_open(document: &Document) -> Result<(), NotaryError> {
+        if matches!(document.status, DocumentStatus::Revoked | DocumentStatus::Expired) {
+            return Err(NotaryError::InvalidStatus);
         }
+        Ok(())
+    }
 
-        let current_version_idx = document.current_version as usize;
-        let mut current_version = document.versions.get(current_version_idx as u32).unwrap().clone();
+    /// Helper: Check whether a document's expiry has passed
+    fn is_expired(env: &Env, document: &Document) -> bool {
+        document.expires_at != 0 && env.ledger().timestamp() >= document.expires_at
+    }
 
-        if current_version.signatures.iter().any(|s| s.signer == signature.signer) {
-            return Err(NotaryError::AlreadyExists);
+    /// Helper: Expiry timestamp from the EXP_DAYS metadata override or the global setting
+    fn compute_expiry(env: &Env, metadata: &Map<Symbol, String>) -> Result<u64, NotaryError> {
+        let days = match metadata.get(EXP_DAYS) {
+            Some(value) => Self::parse_u64(&value)
+                .filter(|days| *days <= MAX_EXPIRY_DAYS)
+                .ok_or(NotaryError::InvalidInput)?,
+            None => Self::load_config(env).expiry_days,
+        };
+
+        if days == 0 {
+            return Ok(0);
+        }
+        Ok(env.ledger().timestamp() + days * SECONDS_PER_DAY)
+    }
+
+    /// Helper: Parse a decimal string stored as a metadata value
+    fn parse_u64(value: &String) -> Option<u64> {
+        let len = value.len() as usize;
+        if len == 0 || len > 20 {
+            return None;
         }
 
-        current_version.signatures.push_back(signature);
-        current_version.updated_at = env.ledger().timestamp();
+        let mut buf = [0u8; 20];
+        value.copy_into_slice(&mut buf[..len]);
+        buf[..len].iter().try_fold(0u64, |acc, b| {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            acc.checked_mul(10)?.checked_add((b - b'0') as u64)
+        })
+    }
 
-        if current_version.signatures.len() == current_version.required_signers.len() {
-            current_version.status = VersionStatus::Approved;
-            document.status = DocumentStatus::Active;
+    /// Helper: Write a document back to its own storage entry, bumping its TTL. `previous`
+    /// is where it was listed when loaded, `None` for a new document.
+    fn save_document(env: &Env, document: &Document, previous: Option<&Listing>) {
+        let key = DataKey::Document(document.hash.clone());
+        env.storage().persistent().set(&key, document);
+        Self::bump(env, &key);
+        Self::reindex_document(env, previous, document);
+    }
+
+    /// Helper: Bring the signer, awaiting-signature and status lists in line with a
+    /// document that is being saved over its `previous` listing
+    fn reindex_document(env: &Env, previous: Option<&Listing>, document: &Document) {
+        let empty = Vec::new(env);
+        let (old_signers, old_awaiting) = match previous {
+            Some(previous) => (previous.signers.clone(), previous.awaiting.clone()),
+            None => (empty.clone(), empty),
+        };
+        Self::reindex_members(env, DocIndex::Signer, &document.hash, &old_signers, &document.authorized_signers);
+        Self::reindex_members(env, DocIndex::Awaiting, &document.hash, &old_awaiting, &Self::awaiting_signers(env, document));
+
+        let old_status = previous.map(|previous| previous.status.clone());
+        if old_status.as_ref() != Some(&document.status) {
+            if let Some(old_status) = old_status {
+                Self::index_remove(env, DocIndex::Status(old_status), &document.hash);
+            }
+            Self::index_push(env, DocIndex::Status(document.status.clone()), &document.hash);
         }
+    }
 
-        document.versions.set(current_version_idx as u32, current_version);
-        document.updated_at = env.ledger().timestamp();
+    /// Helper: Move a document between per-address lists as an address set changes
+    fn reindex_members(
+        env: &Env,
+        index: fn(Address) -> DocIndex,
+        hash: &BytesN<32>,
+        old: &Vec<Address>,
+        new: &Vec<Address>,
+    ) {
+        for address in old.iter() {
+            if !new.contains(&address) {
+                Self::index_remove(env, index(address), hash);
+            }
+        }
+        for address in new.iter() {
+            if !old.contains(&address) {
+                Self::index_push(env, index(address), hash);
+            }
+        }
+    }
 
-        state.documents.set(document_hash.clone(), document);
-        env.storage().instance().set(&STATE, &state);
+    /// Helper: Signers whose signature the latest version is waiting on. In ordered mode
+    /// only the next signer is waited on.
+    fn awaiting_signers(env: &Env, document: &Document) -> Vec<Address> {
+        let version = document.versions.get(document.latest_version).unwrap();
+        if version.status != VersionStatus::PendingApproval
+            || matches!(document.status, DocumentStatus::Revoked | DocumentStatus::Expired)
+        {
+            return Vec::new(env);
+        }
 
-        env.events().publish((DOCS,), NotaryEvent::DocumentSigned(document_hash));
+        if version.ordered {
+            return Self::next_signer(&version).map_or(Vec::new(env), |signer| vec![env, signer]);
+        }
+
+        let mut signers = Vec::new(env);
+        for signer in version.required_signers.iter() {
+            if !version.signatures.iter().any(|s| s.signer == signer) {
+                signers.push_back(signer);
+            }
+        }
+        signers
+    }
+
+    /// Helper: Record which document and position a version hash belongs to. Each
+    /// hash may be registered only once across all documents.
+    fn index_version(
+        env: &Env,
+        version_hash: &BytesN<32>,
+        document_hash: &BytesN<32>,
+        version_index: u32,
+    ) -> Result<(), NotaryError> {
+        let key = DataKey::Version(version_hash.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(NotaryError::AlreadyExists);
+        }
 
+        let version_ref = VersionRef { document_hash: document_hash.clone(), version_index };
+        env.storage().persistent().set(&key, &version_ref);
+        Self::bump(env, &key);
         Ok(())
     }
 
-    /// Register a certification authority
-    pub fn register_authority(env: Env, authority: Address) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+    /// Helper: Number of slots in a list, counting slots emptied by removals
+    fn index_len(env: &Env, index: &DocIndex) -> u32 {
+        env.storage().persistent().get(&DataKey::IndexLen(index.clone())).unwrap_or(0)
+    }
 
-        if env.current_contract_address() != state.admin {
-            return Err(NotaryError::Unauthorized);
+    /// Helper: Append a document to a list unless it is already there
+    fn index_push(env: &Env, index: DocIndex, hash: &BytesN<32>) {
+        let pos_key = DataKey::IndexPos(index.clone(), hash.clone());
+        if env.storage().persistent().has(&pos_key) {
+            return;
         }
+        let len = Self::index_len(env, &index);
 
-        if !state.authorities.contains(&authority) {
-            state.authorities.push_back(authority.clone());
-            env.storage().instance().set(&STATE, &state);
-            env.events().publish((AUTH,), NotaryEvent::AuthorityAdded(authority));
+        let item_key = DataKey::IndexItem(index.clone(), len);
+        env.storage().persistent().set(&item_key, hash);
+        Self::bump(env, &item_key);
+        env.storage().persistent().set(&pos_key, &len);
+        Self::bump(env, &pos_key);
+
+        let len_key = DataKey::IndexLen(index);
+        env.storage().persistent().set(&len_key, &(len + 1));
+        Self::bump(env, &len_key);
+    }
+
+    /// Helper: Drop a document from a list, leaving its slot empty so later entries keep
+    /// their positions and offset paging neither skips nor repeats them. Empty slots at the
+    /// end of the list are trimmed, at most a page's worth per removal.
+    fn index_remove(env: &Env, index: DocIndex, hash: &BytesN<32>) {
+        let storage = env.storage().persistent();
+        let pos_key = DataKey::IndexPos(index.clone(), hash.clone());
+        let Some(position) = storage.get::<_, u32>(&pos_key) else {
+            return;
+        };
+
+        storage.remove(&DataKey::IndexItem(index.clone(), position));
+        storage.remove(&pos_key);
+
+        if position + 1 != Self::index_len(env, &index) {
+            return;
         }
 
-        Ok(())
+        let floor = position.saturating_sub(MAX_PAGE_SIZE);
+        let mut len = position;
+        while len > floor && !storage.has(&DataKey::IndexItem(index.clone(), len - 1)) {
+            len -= 1;
+        }
+
+        let len_key = DataKey::IndexLen(index);
+        storage.set(&len_key, &len);
+        Self::bump(env, &len_key);
     }
 
-    /// Add identity claim
-    pub fn add_claim(
-        env: Env,
-        user: Address,
-        claim: IdentityClaim,
-    ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+    /// Helper: Read positions `[offset, offset + limit)` of a list, skipping slots emptied by
+    /// removals and keeping only documents listed under `status` when given. The filter
+    /// checks the status index rather than loading each document, so lapsed documents match
+    /// their stored status. Pages may hold fewer than `limit` entries, so callers continue
+    /// from `next_offset` rather than counting results.
+    fn index_page(
+        env: &Env,
+        index: &DocIndex,
+        offset: u32,
+        limit: u32,
+        status: Option<DocumentStatus>,
+    ) -> Result<DocumentPage, NotaryError> {
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            log!(env, "page size out of range", limit, MAX_PAGE_SIZE);
+            return Err(NotaryError::InvalidInput);
+        }
 
-        if !state.authorities.contains(&env.current_contract_address()) {
-            return Err(NotaryError::InvalidAuthority);
+        let len = Self::index_len(env, index);
+        let end = offset.saturating_add(limit).min(len);
+        let mut documents = Vec::new(env);
+
+        for position in offset..end {
+            let Some(hash) = env.storage().persistent()
+                .get::<_, BytesN<32>>(&DataKey::IndexItem(index.clone(), position))
+            else {
+                continue;
+            };
+            let matches = match &status {
+                Some(status) => env.storage().persistent()
+                    .has(&DataKey::IndexPos(DocIndex::Status(status.clone()), hash.clone())),
+                None => true,
+            };
+            if matches {
+                documents.push_back(hash);
+            }
         }
 
-        if claim.expires_at <= env.ledger().timestamp() {
-            return Err(NotaryError::ExpiredClaim);
+        let next_offset = if end < len { Some(end) } else { None };
+        Ok(DocumentPage { documents, next_offset })
+    }
+
+    /// Helper: Message a signer must sign to approve a document version
+    fn build_signing_payload(
+        env: &Env,
+        document_hash: &BytesN<32>,
+        version_index: u32,
+        timestamp: u64,
+    ) -> Bytes {
+        let mut payload: Bytes = document_hash.clone().into();
+        payload.extend_from_array(&version_index.to_be_bytes());
+        payload.append(&env.current_contract_address().to_xdr(env));
+        payload.extend_from_array(&timestamp.to_be_bytes());
+        payload
+    }
+
+    /// Helper: Verify a recent signature against the signer's key valid now, so a key
+    /// retired by rotation or revocation cannot back a signature dated before that.
+    /// Returns `InvalidSignature` when no key applies; a signature that fails ed25519
+    /// verification aborts the invocation in the host instead.
+    fn verify_signature(
+        env: &Env,
+        document_hash: &BytesN<32>,
+        version_index: u32,
+        signature: &Signature,
+    ) -> Result<(), NotaryError> {
+        let now = env.ledger().timestamp();
+        if signature.timestamp > now || now - signature.timestamp > SIGNATURE_MAX_AGE {
+            return Err(NotaryError::InvalidSignature);
         }
 
-        let mut user_claims = state.claims.get(user.clone())
-            .unwrap_or(Vec::new(&env));
-        user_claims.push_back(claim);
-        state.claims.set(user.clone(), user_claims);
+        let public_key = Self::resolve_signing_key(env, &signature.signer, now)
+            .ok_or(NotaryError::InvalidSignature)?;
 
-        env.storage().instance().set(&STATE, &state);
-        env.events().publish((AUTH,), NotaryEvent::ClaimAdded(user));
+        let payload = Self::build_signing_payload(env, document_hash, version_index, signature.timestamp);
+        env.crypto().ed25519_verify(&public_key, &payload, &signature.signature_data);
 
         Ok(())
     }
 
-    /// Verify document
-    pub fn verify_document(env: Env, document_hash: BytesN<32>) -> Result<Document, NotaryError> {
-        let state: NotaryState = env.storage().instance().get(&STATE).unwrap();
-        
-        state.documents.get(document_hash)
-            .ok_or(NotaryError::NotFound)
+    /// Helper: Message an authority must sign to issue an identity claim
+    fn build_claim_payload(
+        env: &Env,
+        user: &Address,
+        claim_type: &Symbol,
+        claim_value: &BytesN<32>,
+        issued_at: u64,
+        expires_at: u64,
+    ) -> Bytes {
+        let mut payload = user.clone().to_xdr(env);
+        payload.append(&claim_type.clone().to_xdr(env));
+        payload.append(&claim_value.clone().into());
+        payload.extend_from_array(&issued_at.to_be_bytes());
+        payload.extend_from_array(&expires_at.to_be_bytes());
+        payload
     }
 
-    /// Get user's documents
-    pub fn get_user_documents(env: Env, user: Address) -> Result<Vec<BytesN<32>>, NotaryError> {
-        let state: NotaryState = env.storage().instance().get(&STATE).unwrap();
-        
-        Ok(state.user_documents.get(user)
-            .unwrap_or(Vec::new(&env)).clone())
+    /// Helper: Verify a claim's signature against the issuing authority's key valid now.
+    /// Returns `InvalidSignature` when no key applies; a signature that fails ed25519
+    /// verification aborts the invocation in the host instead.
+    fn verify_claim_signature(
+        env: &Env,
+        user: &Address,
+        claim: &IdentityClaim,
+    ) -> Result<(), NotaryError> {
+        let public_key = Self::resolve_signing_key(env, &claim.authority, env.ledger().timestamp())
+            .ok_or(NotaryError::InvalidSignature)?;
+
+        let payload = Self::build_claim_payload(
+            env,
+            user,
+            &claim.claim_type,
+            &claim.claim_value,
+            claim.issued_at,
+            claim.expires_at,
+        );
+        env.crypto().ed25519_verify(&public_key, &payload, &claim.signature);
+
+        Ok(())
     }
 
-    /// Update document status
-    pub fn update_status(
-        env: Env,
-        document_hash: BytesN<32>,
-        new_status: DocumentStatus,
-    ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+    /// Helper: Content-derived identifier of an identity claim
+    fn compute_claim_id(env: &Env, claim: &IdentityClaim) -> BytesN<32> {
+        env.crypto().sha256(&claim.clone().to_xdr(env)).into()
+    }
+
+    /// Helper: Fail if a claim has expired, been revoked, or comes from an authority
+    /// that is no longer active or was registered again after issuing it
+    fn check_claim(env: &Env, claim_id: &BytesN<32>, claim: &IdentityClaim) -> Result<(), NotaryError> {
+        if claim.expires_at <= env.ledger().timestamp() {
+            return Err(NotaryError::ExpiredClaim);
+        }
+
+        if env.storage().persistent().has(&DataKey::ClaimRevocation(claim_id.clone())) {
+            return Err(NotaryError::RevokedClaim);
+        }
+
+        match Self::active_authority(env, &claim.authority) {
+            Some(record) if claim.issued_at >= record.registered_at => Ok(()),
+            _ => Err(NotaryError::InvalidAuthority),
+        }
+    }
 
-        let mut document = state.documents.get(document_hash.clone())
+    /// Helper: Find a claim by id along with the user holding it
+    fn find_claim(env: &Env, claim_id: &BytesN<32>) -> Result<(Address, IdentityClaim), NotaryError> {
+        let user: Address = env.storage().persistent().get(&DataKey::ClaimHolder(claim_id.clone()))
             .ok_or(NotaryError::NotFound)?;
+        let claim = Self::load_claim(env, claim_id).ok_or(NotaryError::NotFound)?;
+        Ok((user, claim))
+    }
 
-        if env.current_contract_address() != document.owner {
-            return Err(NotaryError::Unauthorized);
+    /// Helper: Load a claim from its own storage entry
+    fn load_claim(env: &Env, claim_id: &BytesN<32>) -> Option<IdentityClaim> {
+        env.storage().persistent().get(&DataKey::Claim(claim_id.clone()))
+    }
+
+    /// Helper: Ids of the claims listed for a user
+    fn load_claim_ids(env: &Env, user: &Address) -> Vec<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::Claims(user.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Helper: Whether a stored claim has expired or been revoked, which it cannot recover from
+    fn is_claim_spent(env: &Env, claim_id: &BytesN<32>) -> bool {
+        match Self::load_claim(env, claim_id) {
+            Some(claim) => matches!(
+                Self::check_claim(env, claim_id, &claim),
+                Err(NotaryError::ExpiredClaim | NotaryError::RevokedClaim)
+            ),
+            None => true,
         }
+    }
 
-        document.status = new_status.clone();
-        document.updated_at = env.ledger().timestamp();
+    /// Helper: Parse a claim type stored as a string setting or metadata value
+    fn parse_claim_type(env: &Env, value: &String) -> Option<Symbol> {
+        let len = value.len() as usize;
+        if len == 0 || len > 32 {
+            return None;
+        }
 
-        state.documents.set(document_hash.clone(), document);
-        env.storage().instance().set(&STATE, &state);
+        let mut buf = [0u8; 32];
+        value.copy_into_slice(&mut buf[..len]);
+        if !buf[..len].iter().all(|b| b.is_ascii_alphanumeric() || *b == b'_') {
+            return None;
+        }
 
-        env.events().publish((DOCS,), NotaryEvent::StatusChanged(document_hash, new_status));
+        core::str::from_utf8(&buf[..len]).ok()
+            .map(|s| Symbol::new(env, s))
+    }
 
+    /// Helper: Fail unless a VER_REQ metadata value names a valid claim type
+    fn validate_claim_requirement(env: &Env, metadata: &Map<Symbol, String>) -> Result<(), NotaryError> {
+        if let Some(value) = metadata.get(VER_REQ) {
+            Self::parse_claim_type(env, &value).ok_or(NotaryError::InvalidInput)?;
+        }
         Ok(())
     }
 
-    /// Get contract configuration
-    pub fn get_config(env: Env, key: Symbol) -> Result<String, NotaryError> {
-        let state: NotaryState = env.storage().instance().get(&STATE).unwrap();
-        
-        state.settings.get(key)
-            .ok_or(NotaryError::NotFound)
+    /// Helper: Claim types a signer must hold; the global setting always applies and
+    /// document metadata can only add to it
+    fn required_claim_types(env: &Env, document: &Document) -> Result<Vec<Symbol>, NotaryError> {
+        let mut claim_types = Vec::new(env);
+        if let Some(claim_type) = Self::load_config(env).required_claim_type {
+            claim_types.push_back(claim_type);
+        }
+        if let Some(value) = document.metadata.get(VER_REQ) {
+            let claim_type = Self::parse_claim_type(env, &value).ok_or(NotaryError::InvalidState)?;
+            if !claim_types.contains(&claim_type) {
+                claim_types.push_back(claim_type);
+            }
+        }
+        Ok(claim_types)
     }
 
-    /// Update contract configuration
-    pub fn update_config(
-        env: Env,
-        key: Symbol,
-        value: String,
+    /// Helper: Check the signer holds the referenced, live identity claim and one of each required type
+    fn verify_identity_claim(
+        env: &Env,
+        document: &Document,
+        signature: &Signature,
     ) -> Result<(), NotaryError> {
-        let mut state: NotaryState = env.storage().instance().get(&STATE).unwrap();
+        let (holder, claim) = Self::find_claim(env, &signature.claim_reference)
+            .map_err(|_| NotaryError::MissingIdentityClaim)?;
+        if holder != signature.signer {
+            return Err(NotaryError::MissingIdentityClaim);
+        }
 
-        if env.current_contract_address() != state.admin {
-            return Err(NotaryError::Unauthorized);
+        Self::check_claim(env, &signature.claim_reference, &claim)?;
+
+        // Each required type must be covered by the referenced claim or another live one
+        for claim_type in Self::required_claim_types(env, document)?.iter() {
+            if claim.claim_type == claim_type {
+                continue;
+            }
+            let held = Self::load_claim_ids(env, &signature.signer).iter().any(|id| {
+                Self::load_claim(env, &id).is_some_and(|c| {
+                    c.claim_type == claim_type && Self::check_claim(env, &id, &c).is_ok()
+                })
+            });
+            if !held {
+                return Err(NotaryError::MissingIdentityClaim);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Helper: Load all signing keys registered for an address
+    fn load_signer_keys(env: &Env, signer: &Address) -> Vec<SignerKey> {
+        env.storage().persistent().get(&DataKey::SignerKeys(signer.clone()))
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Helper: Whether a key is neither revoked nor past its validity window
+    fn is_live(key: &SignerKey, now: u64) -> bool {
+        key.revoked_at == 0 && key.valid_until > now
+    }
+
+    /// Helper: Number of live keys in a key list, leaving out `excluded` if given
+    fn live_key_count(env: &Env, keys: &Vec<SignerKey>, excluded: Option<&BytesN<32>>) -> u32 {
+        let now = env.ledger().timestamp();
+        keys.iter()
+            .filter(|k| Self::is_live(k, now) && Some(&k.public_key) != excluded)
+            .count() as u32
+    }
+
+    /// Helper: Fail if a public key is already on record; revoked keys can never return
+    fn require_new_key(keys: &Vec<SignerKey>, public_key: &BytesN<32>) -> Result<(), NotaryError> {
+        match keys.iter().find(|k| k.public_key == *public_key) {
+            Some(key) if key.revoked_at != 0 => Err(NotaryError::InvalidState),
+            Some(_) => Err(NotaryError::AlreadyExists),
+            None => Ok(()),
+        }
+    }
+
+    /// Helper: Store an address's signing keys
+    fn save_signer_keys(env: &Env, signer: &Address, keys: &Vec<SignerKey>) {
+        let key = DataKey::SignerKeys(signer.clone());
+        env.storage().persistent().set(&key, keys);
+        Self::bump(env, &key);
+    }
+
+    /// Helper: Find the key valid at `timestamp`, preferring the most recently activated one.
+    /// Revoked keys never resolve, whatever the timestamp.
+    fn resolve_signing_key(env: &Env, signer: &Address, timestamp: u64) -> Option<BytesN<32>> {
+        let mut resolved: Option<SignerKey> = None;
+        for key in Self::load_signer_keys(env, signer).iter() {
+            if key.revoked_at != 0 || timestamp < key.valid_from || timestamp >= key.valid_until {
+                continue;
+            }
+            if resolved.as_ref().is_none_or(|r| key.valid_from >= r.valid_from) {
+                resolved = Some(key);
+            }
+        }
+        resolved.map(|key| key.public_key)
+    }
+
+    /// Helper: The record of a registered authority that is not suspended
+    fn active_authority(env: &Env, authority: &Address) -> Option<AuthorityRecord> {
+        Self::load_authority(env, authority).ok()
+            .filter(|record| record.suspended_reason.is_none())
+    }
+
+    /// Helper: Load a registered authority's record for an update
+    fn load_authority(env: &Env, authority: &Address) -> Result<AuthorityRecord, NotaryError> {
+        let key = DataKey::Authority(authority.clone());
+        let record = env.storage().persistent().get(&key)
+            .ok_or(NotaryError::NotFound)?;
+        Self::bump(env, &key);
+        Ok(record)
+    }
+
+    /// Helper: Store an authority's record
+    fn save_authority(env: &Env, authority: &Address, record: &AuthorityRecord) {
+        let key = DataKey::Authority(authority.clone());
+        env.storage().persistent().set(&key, record);
+        Self::bump(env, &key);
+    }
+
+    /// Helper: Transfer the notarization fee from `payer` to the treasury.
+    /// Active authorities are exempt.
+    fn charge_fee(env: &Env, payer: &Address) -> Result<(), NotaryError> {
+        let amount = Self::load_config(env).fee_amount;
+        if amount == 0 || Self::active_authority(env, payer).is_some() {
+            return Ok(());
+        }
+
+        let fees: FeeConfig = env.storage().instance().get(&FEE_CFG)
+            .ok_or(NotaryError::InvalidState)?;
+
+        token::Client::new(env, &fees.token).transfer(payer, &fees.treasury, &amount);
+        env.events().publish((FEES,), NotaryEvent::FeeCharged(payer.clone(), amount));
+
+        Ok(())
+    }
+
+    /// Helper: Read the contract configuration, falling back to defaults
+    fn load_config(env: &Env) -> NotaryConfig {
+        env.storage().instance().get(&CONFIG)
+            .unwrap_or(NotaryConfig {
+                max_signers: DEFAULT_MAX_SIGNERS,
+                min_signers: DEFAULT_MIN_SIGNERS,
+                expiry_days: 0,
+                fee_amount: 0,
+                required_claim_type: None,
+                retention_days: DEFAULT_RETENTION_DAYS,
+            })
+    }
+
+    /// Helper: Minimum retention period in ledgers, capped by the network maximum
+    fn retention_ledgers(env: &Env) -> u32 {
+        Self::load_config(env).retention_days
+            .saturating_mul(DAY_IN_LEDGERS)
+            .min(env.storage().max_ttl())
+    }
+
+    /// Helper: Keep a persistent entry, and the instance with it, alive for at least the
+    /// retention period
+    fn bump(env: &Env, key: &DataKey) {
+        let extend_to = Self::retention_ledgers(env);
+        env.storage().persistent().extend_ttl(key, extend_to.saturating_sub(DAY_IN_LEDGERS), extend_to);
+        Self::extend_instance(env, extend_to);
+    }
+
+    /// Helper: Every persistent entry tied to a stored document: the document itself,
+    /// its version and rejection records, and its positions in each document list
+    fn document_keys(env: &Env, document: &Document) -> Vec<DataKey> {
+        let hash = &document.hash;
+        let mut keys = vec![env, DataKey::Document(hash.clone())];
+
+        for (index, version) in document.versions.iter().enumerate() {
+            keys.push_back(DataKey::Version(version.hash.clone()));
+            if version.status == VersionStatus::Rejected {
+                keys.push_back(DataKey::Rejection(hash.clone(), index as u32));
+            }
+        }
+
+        let mut indexes = vec![
+            env,
+            DocIndex::Owner(document.owner.clone()),
+            DocIndex::Status(document.status.clone()),
+        ];
+        for signer in document.authorized_signers.iter() {
+            indexes.push_back(DocIndex::Signer(signer));
+        }
+        for signer in Self::awaiting_signers(env, document).iter() {
+            indexes.push_back(DocIndex::Awaiting(signer));
+        }
+        for index in indexes.iter() {
+            let pos_key = DataKey::IndexPos(index.clone(), hash.clone());
+            if let Some(position) = env.storage().persistent().get::<_, u32>(&pos_key) {
+                keys.push_back(pos_key);
+                keys.push_back(DataKey::IndexItem(index.clone(), position));
+                keys.push_back(DataKey::IndexLen(index));
+            }
+        }
+        keys
+    }
+
+    /// Helper: Keep the contract instance alive for at least the retention period
+    fn bump_instance(env: &Env) {
+        Self::extend_instance(env, Self::retention_ledgers(env));
+    }
+
+    /// Helper: Extend the contract instance to `extend_to` ledgers once within a day of it
+    fn extend_instance(env: &Env, extend_to: u32) {
+        env.storage().instance().extend_ttl(extend_to.saturating_sub(DAY_IN_LEDGERS), extend_to);
+    }
+
+    /// Add new version to document, branching from `parent_version` or else the latest version
+    pub fn add_version(
+        env: Env,
+        caller: Address,
+        document_hash: BytesN<32>,
+        version_hash: BytesN<32>,
+        title: String,
+        metadata: Map<Symbol, String>,
+        parent_version: Option<u32>,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if !Self::is_authorized(&document, &caller) {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::require_open(&document)?;
+
+        let parent = document.versions
+            .get(parent_version.unwrap_or(document.latest_version))
+            .ok_or(NotaryError::InvalidVersion)?;
+
+        Self::charge_fee(&env, &caller)?;
+
+        let version = DocumentVersion {
+            hash: version_hash.clone(),
+            parent_hash: parent.hash,
+            title,
+            status: VersionStatus::Draft,
+            creator: caller,
+            created_at: env.ledger().timestamp(),
+            updated_at: env.ledger().timestamp(),
+            signatures: Vec::new(&env),
+            required_signers: document.authorized_signers.clone(),
+            threshold: document.threshold,
+            signer_weights: document.signer_weights.clone(),
+            required_weight: document.required_weight,
+            ordered: document.ordered,
+            metadata,
+        };
+
+        document.versions.push_back(version);
+        document.latest_version = document.versions.len() - 1;
+        document.updated_at = env.ledger().timestamp();
+
+        Self::index_version(&env, &version_hash, &document_hash, document.latest_version)?;
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::VersionAdded(version_hash));
+
+        Ok(())
+    }
+
+    /// Helper: Index of the closest version before `index` whose hash is `parent_hash`
+    fn find_parent(document: &Document, index: u32, parent_hash: &BytesN<32>) -> Option<u32> {
+        (0..index).rev().find(|i| document.versions.get(*i).unwrap().hash == *parent_hash)
+    }
+
+    /// Helper: The latest version if it is still a draft that `caller` may edit or submit
+    fn latest_draft(document: &Document, caller: &Address) -> Result<DocumentVersion, NotaryError> {
+        let version = document.versions.get(document.latest_version).unwrap();
+
+        if *caller != document.owner && *caller != version.creator {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        if version.status != VersionStatus::Draft {
+            return Err(NotaryError::InvalidStatus);
+        }
+        Ok(version)
+    }
+
+    /// Edit the title and metadata of the latest version while it is still a draft
+    pub fn update_draft(
+        env: Env,
+        caller: Address,
+        document_hash: BytesN<32>,
+        title: String,
+        metadata: Map<Symbol, String>,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+        let mut version = Self::latest_draft(&document, &caller)?;
+        Self::require_open(&document)?;
+
+        version.title = title;
+        version.metadata = metadata;
+        version.updated_at = env.ledger().timestamp();
+        document.versions.set(document.latest_version, version);
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        Ok(())
+    }
+
+    /// Freeze the latest draft and open it for signatures
+    pub fn submit_version(env: Env, caller: Address, document_hash: BytesN<32>) -> Result<(), NotaryError> {
+        caller.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+        let mut version = Self::latest_draft(&document, &caller)?;
+        Self::require_open(&document)?;
+
+        version.status = VersionStatus::PendingApproval;
+        version.updated_at = env.ledger().timestamp();
+        document.versions.set(document.latest_version, version);
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::VersionSubmitted(document_hash, document.latest_version));
+
+        Ok(())
+    }
+
+    /// Sign the latest version of a document as `signer` once it awaits approval
+    pub fn sign_document(
+        env: Env,
+        signer: Address,
+        document_hash: BytesN<32>,
+        signature: Signature,
+    ) -> Result<(), NotaryError> {
+        signer.require_auth();
+        Self::require_not_paused(&env)?;
+
+        if signature.signer != signer {
+            return Err(NotaryError::InvalidSignature);
+        }
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if !document.authorized_signers.contains(signer) {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::require_open(&document)?;
+
+        let latest_version_idx = document.latest_version;
+        let mut latest_version = document.versions.get(latest_version_idx).unwrap();
+
+        if latest_version.status != VersionStatus::PendingApproval {
+            return Err(NotaryError::InvalidStatus);
+        }
+
+        if latest_version.signatures.iter().any(|s| s.signer == signature.signer) {
+            return Err(NotaryError::AlreadyExists);
+        }
+
+        if latest_version.ordered
+            && Self::next_signer(&latest_version) != Some(signature.signer.clone())
+        {
+            return Err(NotaryError::InvalidState);
+        }
+
+        Self::verify_identity_claim(&env, &document, &signature)?;
+        Self::verify_signature(&env, &document_hash, latest_version_idx, &signature)?;
+
+        latest_version.signatures.push_back(signature);
+        latest_version.updated_at = env.ledger().timestamp();
+
+        let approved = Self::is_approved(&latest_version);
+        if approved {
+            latest_version.status = VersionStatus::Approved;
+            document.status = DocumentStatus::Active;
+        }
+
+        document.versions.set(latest_version_idx, latest_version);
+        if approved {
+            Self::supersede_versions(&mut document, latest_version_idx);
+        }
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::DocumentSigned(document_hash));
+
+        Ok(())
+    }
+
+    /// Reject the latest version as one of its required signers while it awaits approval,
+    /// recording a hash of the reason. A rejected version can no longer be signed.
+    pub fn reject_version(
+        env: Env,
+        document_hash: BytesN<32>,
+        version_index: u32,
+        signer: Address,
+        reason_hash: BytesN<32>,
+    ) -> Result<(), NotaryError> {
+        signer.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if version_index != document.latest_version {
+            return Err(NotaryError::InvalidVersion);
+        }
+
+        let mut version = document.versions.get(version_index).unwrap();
+
+        if !version.required_signers.contains(&signer) {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::require_open(&document)?;
+
+        if version.status != VersionStatus::PendingApproval {
+            return Err(NotaryError::InvalidStatus);
+        }
+
+        if version.signatures.iter().any(|s| s.signer == signer) {
+            return Err(NotaryError::InvalidState);
+        }
+
+        let rejection = Rejection {
+            signer: signer.clone(),
+            reason_hash,
+            rejected_at: env.ledger().timestamp(),
+        };
+        let key = DataKey::Rejection(document_hash.clone(), version_index);
+        env.storage().persistent().set(&key, &rejection);
+        Self::bump(&env, &key);
+
+        version.status = VersionStatus::Rejected;
+        version.updated_at = env.ledger().timestamp();
+        document.versions.set(version_index, version);
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::VersionRejected(document_hash, version_index, signer));
+
+        Ok(())
+    }
+
+    /// Get who rejected a version and the hash of their reason
+    pub fn get_rejection(env: Env, document_hash: BytesN<32>, version_index: u32) -> Result<Rejection, NotaryError> {
+        env.storage().persistent().get(&DataKey::Rejection(document_hash, version_index))
+            .ok_or(NotaryError::NotFound)
+    }
+
+    /// Bind an ed25519 public key to `signer`, valid in `[valid_from, valid_until)`
+    pub fn register_signing_key(
+        env: Env,
+        signer: Address,
+        public_key: BytesN<32>,
+        valid_from: u64,
+        valid_until: u64,
+    ) -> Result<(), NotaryError> {
+        signer.require_auth();
+
+        if valid_until <= valid_from {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        let mut keys = Self::load_signer_keys(&env, &signer);
+        Self::require_new_key(&keys, &public_key)?;
+        if Self::live_key_count(&env, &keys, None) >= MAX_SIGNING_KEYS {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        keys.push_back(SignerKey {
+            public_key: public_key.clone(),
+            valid_from,
+            valid_until,
+            revoked_at: 0,
+        });
+        Self::save_signer_keys(&env, &signer, &keys);
+
+        env.events().publish((KEYS,), NotaryEvent::KeyRegistered(signer, public_key));
+
+        Ok(())
+    }
+
+    /// Replace `old_key` with `new_key`; the old key stops being valid now
+    pub fn rotate_signing_key(
+        env: Env,
+        signer: Address,
+        old_key: BytesN<32>,
+        new_key: BytesN<32>,
+        valid_until: u64,
+    ) -> Result<(), NotaryError> {
+        signer.require_auth();
+
+        let now = env.ledger().timestamp();
+        if valid_until <= now {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        let mut keys = Self::load_signer_keys(&env, &signer);
+        Self::require_new_key(&keys, &new_key)?;
+
+        let index = keys.iter().position(|k| k.public_key == old_key && Self::is_live(&k, now))
+            .ok_or(NotaryError::NotFound)? as u32;
+        if Self::live_key_count(&env, &keys, Some(&old_key)) >= MAX_SIGNING_KEYS {
+            return Err(NotaryError::InvalidInput);
+        }
+        let mut old = keys.get(index).unwrap();
+        old.valid_until = old.valid_until.min(now);
+        keys.set(index, old);
+
+        keys.push_back(SignerKey {
+            public_key: new_key.clone(),
+            valid_from: now,
+            valid_until,
+            revoked_at: 0,
+        });
+        Self::save_signer_keys(&env, &signer, &keys);
+
+        env.events().publish((KEYS,), NotaryEvent::KeyRotated(signer, new_key));
+
+        Ok(())
+    }
+
+    /// Revoke a signing key; it will no longer verify any signature
+    pub fn revoke_signing_key(
+        env: Env,
+        signer: Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), NotaryError> {
+        signer.require_auth();
+
+        let mut keys = Self::load_signer_keys(&env, &signer);
+        let index = keys.iter().position(|k| k.public_key == public_key && k.revoked_at == 0)
+            .ok_or(NotaryError::NotFound)? as u32;
+        let mut key = keys.get(index).unwrap();
+        key.revoked_at = env.ledger().timestamp();
+        keys.set(index, key);
+        Self::save_signer_keys(&env, &signer, &keys);
+
+        env.events().publish((KEYS,), NotaryEvent::KeyRevoked(signer, public_key));
+
+        Ok(())
+    }
+
+    /// Get every signing key registered for `signer`, including expired and revoked ones,
+    /// which stay on record so signatures can be traced to the key valid at the time.
+    pub fn get_signing_keys(env: Env, signer: Address) -> Vec<SignerKey> {
+        Self::load_signer_keys(&env, &signer)
+    }
+
+    /// Get the public key that verifies `signer`'s signatures made at `timestamp`
+    pub fn get_signing_key(env: Env, signer: Address, timestamp: u64) -> Result<BytesN<32>, NotaryError> {
+        Self::resolve_signing_key(&env, &signer, timestamp)
+            .ok_or(NotaryError::NotFound)
+    }
+
+    /// Get the message `signature_data` must sign for a document version
+    pub fn signing_payload(
+        env: Env,
+        document_hash: BytesN<32>,
+        version_index: u32,
+        timestamp: u64,
+    ) -> Bytes {
+        Self::build_signing_payload(&env, &document_hash, version_index, timestamp)
+    }
+
+    /// Register a certification authority
+    pub fn register_authority(env: Env, caller: Address, authority: Address) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::AuthorityManager)?;
+
+        if !env.storage().persistent().has(&DataKey::Authority(authority.clone())) {
+            let record = AuthorityRecord {
+                claim_types: Vec::new(&env),
+                suspended_reason: None,
+                registered_at: env.ledger().timestamp(),
+                updated_at: env.ledger().timestamp(),
+            };
+            Self::save_authority(&env, &authority, &record);
+            env.events().publish((AUTH,), NotaryEvent::AuthorityAdded(authority));
+        }
+
+        Ok(())
+    }
+
+    /// Deregister an authority. Claims it issued stop satisfying signing checks, even if
+    /// it is registered again later.
+    pub fn remove_authority(env: Env, caller: Address, authority: Address) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::AuthorityManager)?;
+
+        Self::load_authority(&env, &authority)?;
+        env.storage().persistent().remove(&DataKey::Authority(authority.clone()));
+
+        env.events().publish((AUTH,), NotaryEvent::AuthorityRemoved(authority));
+
+        Ok(())
+    }
+
+    /// Stop an authority from issuing claims, and its claims from being relied on,
+    /// until it is reinstated
+    pub fn suspend_authority(
+        env: Env,
+        caller: Address,
+        authority: Address,
+        reason: Symbol,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::AuthorityManager)?;
+
+        let mut record = Self::load_authority(&env, &authority)?;
+        if record.suspended_reason.is_some() {
+            return Err(NotaryError::InvalidState);
+        }
+
+        record.suspended_reason = Some(reason.clone());
+        record.updated_at = env.ledger().timestamp();
+        Self::save_authority(&env, &authority, &record);
+
+        env.events().publish((AUTH,), NotaryEvent::AuthoritySuspended(authority, reason));
+
+        Ok(())
+    }
+
+    /// Lift an authority's suspension
+    pub fn reinstate_authority(env: Env, caller: Address, authority: Address) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::AuthorityManager)?;
+
+        let mut record = Self::load_authority(&env, &authority)?;
+        if record.suspended_reason.is_none() {
+            return Err(NotaryError::InvalidState);
+        }
+
+        record.suspended_reason = None;
+        record.updated_at = env.ledger().timestamp();
+        Self::save_authority(&env, &authority, &record);
+
+        env.events().publish((AUTH,), NotaryEvent::AuthorityReinstated(authority));
+
+        Ok(())
+    }
+
+    /// Limit the claim types an authority may issue; an empty list allows any type
+    pub fn set_authority_scope(
+        env: Env,
+        caller: Address,
+        authority: Address,
+        claim_types: Vec<Symbol>,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::AuthorityManager)?;
+
+        let mut record = Self::load_authority(&env, &authority)?;
+        record.claim_types = claim_types.clone();
+        record.updated_at = env.ledger().timestamp();
+        Self::save_authority(&env, &authority, &record);
+
+        env.events().publish((AUTH,), NotaryEvent::AuthorityScopeUpdated(authority, claim_types));
+
+        Ok(())
+    }
+
+    /// Get a registered authority's scope and suspension state
+    pub fn get_authority(env: Env, authority: Address) -> Result<AuthorityRecord, NotaryError> {
+        Self::load_authority(&env, &authority)
+    }
+
+    /// Add identity claim issued by `authority`, returning the claim id
+    pub fn add_claim(
+        env: Env,
+        authority: Address,
+        user: Address,
+        claim: IdentityClaim,
+    ) -> Result<BytesN<32>, NotaryError> {
+        authority.require_auth();
+        Self::require_not_paused(&env)?;
+
+        if claim.authority != authority {
+            return Err(NotaryError::InvalidAuthority);
+        }
+
+        let record = Self::active_authority(&env, &authority)
+            .ok_or(NotaryError::InvalidAuthority)?;
+
+        if claim.issued_at < record.registered_at {
+            return Err(NotaryError::InvalidAuthority);
+        }
+
+        if !record.claim_types.is_empty() && !record.claim_types.contains(&claim.claim_type) {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        if claim.expires_at <= env.ledger().timestamp() {
+            return Err(NotaryError::ExpiredClaim);
+        }
+
+        if claim.issued_at > env.ledger().timestamp() || claim.issued_at >= claim.expires_at {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        Self::verify_claim_signature(&env, &user, &claim)?;
+
+        let claim_id = Self::compute_claim_id(&env, &claim);
+        let claim_key = DataKey::Claim(claim_id.clone());
+        if env.storage().persistent().has(&claim_key) {
+            return Err(NotaryError::AlreadyExists);
+        }
+
+        // Expired and revoked claims make room in the user's list
+        let mut claim_ids = Vec::new(&env);
+        for id in Self::load_claim_ids(&env, &user).iter() {
+            if !Self::is_claim_spent(&env, &id) {
+                claim_ids.push_back(id);
+            }
+        }
+        if claim_ids.len() >= MAX_CLAIMS_PER_USER {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        env.storage().persistent().set(&claim_key, &claim);
+        Self::bump(&env, &claim_key);
+
+        claim_ids.push_back(claim_id.clone());
+        let claims_key = DataKey::Claims(user.clone());
+        env.storage().persistent().set(&claims_key, &claim_ids);
+        Self::bump(&env, &claims_key);
+
+        let holder_key = DataKey::ClaimHolder(claim_id.clone());
+        env.storage().persistent().set(&holder_key, &user);
+        Self::bump(&env, &holder_key);
+
+        env.events().publish((AUTH,), NotaryEvent::ClaimAdded(user));
+
+        Ok(claim_id)
+    }
+
+    /// Revoke a claim before it expires. Only its issuing authority or an authority
+    /// manager may do so.
+    pub fn revoke_claim(
+        env: Env,
+        caller: Address,
+        claim_id: BytesN<32>,
+        reason: Symbol,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (user, claim) = Self::find_claim(&env, &claim_id)?;
+        if caller != claim.authority {
+            Self::require_role(&env, &caller, Role::AuthorityManager)?;
+        }
+
+        let key = DataKey::ClaimRevocation(claim_id.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(NotaryError::AlreadyExists);
+        }
+
+        let revocation = ClaimRevocation {
+            revoked_by: caller,
+            reason: reason.clone(),
+            revoked_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &revocation);
+        Self::bump(&env, &key);
+
+        env.events().publish((AUTH,), NotaryEvent::ClaimRevoked(user, claim_id, reason));
+
+        Ok(())
+    }
+
+    /// Get the revocation record of a revoked claim
+    pub fn get_claim_revocation(env: Env, claim_id: BytesN<32>) -> Result<ClaimRevocation, NotaryError> {
+        env.storage().persistent().get(&DataKey::ClaimRevocation(claim_id))
+            .ok_or(NotaryError::NotFound)
+    }
+
+    /// Check whether a claim exists and is unexpired, unrevoked and backed by an
+    /// active authority
+    pub fn is_claim_valid(env: Env, claim_id: BytesN<32>) -> bool {
+        match Self::find_claim(&env, &claim_id) {
+            Ok((_, claim)) => Self::check_claim(&env, &claim_id, &claim).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// Get the identity claims listed for `user`. Expired and revoked claims are dropped
+    /// from the list whenever a new claim is added.
+    pub fn get_claims(env: Env, user: Address) -> Vec<IdentityClaim> {
+        let mut claims = Vec::new(&env);
+        for id in Self::load_claim_ids(&env, &user).iter() {
+            if let Some(claim) = Self::load_claim(&env, &id) {
+                claims.push_back(claim);
+            }
+        }
+        claims
+    }
+
+    /// Get the message an authority must sign to issue a claim to `user`
+    pub fn claim_payload(
+        env: Env,
+        user: Address,
+        claim_type: Symbol,
+        claim_value: BytesN<32>,
+        issued_at: u64,
+        expires_at: u64,
+    ) -> Bytes {
+        Self::build_claim_payload(&env, &user, &claim_type, &claim_value, issued_at, expires_at)
+    }
+
+    /// Get the id a `Signature.claim_reference` must carry to reference `claim`
+    pub fn get_claim_id(env: Env, claim: IdentityClaim) -> BytesN<32> {
+        Self::compute_claim_id(&env, &claim)
+    }
+
+    /// Get who must sign the latest version next; `None` unless it is awaiting approval.
+    /// Only ordered documents have a next signer.
+    pub fn get_next_signer(env: Env, document_hash: BytesN<32>) -> Result<Option<Address>, NotaryError> {
+        let document = Self::load_document(&env, &document_hash)?;
+        let version = document.versions.get(document.latest_version).unwrap();
+
+        if !version.ordered {
+            return Err(NotaryError::InvalidState);
+        }
+
+        if version.status != VersionStatus::PendingApproval {
+            return Ok(None);
+        }
+        Ok(Self::next_signer(&version))
+    }
+
+    /// Get the hashes from `version_index` back to the initial version, newest first.
+    /// Fails with `InvalidState` if a parent hash does not resolve to an earlier version.
+    pub fn get_version_chain(
+        env: Env,
+        document_hash: BytesN<32>,
+        version_index: u32,
+    ) -> Result<Vec<BytesN<32>>, NotaryError> {
+        let document = Self::load_document(&env, &document_hash)?;
+        let mut version = document.versions.get(version_index).ok_or(NotaryError::InvalidVersion)?;
+        let mut index = version_index;
+        let mut chain = Vec::new(&env);
+        let root = BytesN::from_array(&env, &[0; 32]);
+
+        loop {
+            chain.push_back(version.hash.clone());
+            if index == 0 {
+                if version.parent_hash != root {
+                    return Err(NotaryError::InvalidState);
+                }
+                return Ok(chain);
+            }
+            index = Self::find_parent(&document, index, &version.parent_hash)
+                .ok_or(NotaryError::InvalidState)?;
+            version = document.versions.get(index).unwrap();
+        }
+    }
+
+    /// Look up a version by its own hash, returning where it lives and its current state
+    pub fn verify_version(env: Env, version_hash: BytesN<32>) -> Result<(VersionRef, DocumentVersion), NotaryError> {
+        let key = DataKey::Version(version_hash);
+        let version_ref: VersionRef = env.storage().persistent().get(&key)
+            .ok_or(NotaryError::NotFound)?;
+        Self::bump(&env, &key);
+
+        let document = Self::load_document(&env, &version_ref.document_hash)?;
+        let version = document.versions.get(version_ref.version_index).unwrap();
+        Ok((version_ref, version))
+    }
+
+    /// Verify document
+    pub fn verify_document(env: Env, document_hash: BytesN<32>) -> Result<Document, NotaryError> {
+        Self::load_document(&env, &document_hash)
+    }
+
+    /// Get a page of the documents `user` owns, oldest first. See `index_page` for how
+    /// `offset`, `limit` and the status filter interact.
+    pub fn get_user_documents(
+        env: Env,
+        user: Address,
+        offset: u32,
+        limit: u32,
+        status: Option<DocumentStatus>,
+    ) -> Result<DocumentPage, NotaryError> {
+        Self::index_page(&env, &DocIndex::Owner(user), offset, limit, status)
+    }
+
+    /// Get how many documents `user` owns
+    pub fn get_user_document_count(env: Env, user: Address) -> u32 {
+        Self::index_len(&env, &DocIndex::Owner(user))
+    }
+
+    /// Get a page of the documents listing `signer` among their signers
+    pub fn get_signer_documents(
+        env: Env,
+        signer: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Result<DocumentPage, NotaryError> {
+        Self::index_page(&env, &DocIndex::Signer(signer), offset, limit, None)
+    }
+
+    /// Get a page of the documents whose latest version is waiting on `signer`. Like
+    /// `get_documents_by_status`, this does not load the documents: one that has lapsed
+    /// but not been written to since stays listed, though `sign_document` refuses it.
+    pub fn get_awaiting_signature(
+        env: Env,
+        signer: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Result<DocumentPage, NotaryError> {
+        Self::index_page(&env, &DocIndex::Awaiting(signer), offset, limit, None)
+    }
+
+    /// Get a page of the documents in `status`. Documents that have lapsed but not been
+    /// written to since are still listed under their stored status.
+    pub fn get_documents_by_status(
+        env: Env,
+        status: DocumentStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Result<DocumentPage, NotaryError> {
+        Self::index_page(&env, &DocIndex::Status(status), offset, limit, None)
+    }
+
+    /// Replace a document's signer list. An unsigned latest version picks up the new list
+    /// and an approved one keeps its signers; the call fails with `InvalidState` while
+    /// signatures are being collected.
+    pub fn update_signers(
+        env: Env,
+        owner: Address,
+        document_hash: BytesN<32>,
+        signers: Vec<Address>,
+    ) -> Result<(), NotaryError> {
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if owner != document.owner {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::validate_signers(&env, &signers)?;
+        Self::validate_threshold(&env, document.threshold, signers.len())?;
+        Self::validate_weights(&env, &signers, &document.signer_weights, document.required_weight)?;
+
+        if let Some(mut latest_version) = Self::unsigned_latest_version(&document)? {
+            latest_version.required_signers = signers.clone();
+            latest_version.updated_at = env.ledger().timestamp();
+            document.versions.set(document.latest_version, latest_version);
+        }
+
+        document.authorized_signers = signers;
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::SignersUpdated(document_hash));
+
+        Ok(())
+    }
+
+    /// Change how many signers must sign before a version is approved. Like
+    /// `update_signers`, this fails with `InvalidState` while signatures are being collected.
+    pub fn set_threshold(
+        env: Env,
+        owner: Address,
+        document_hash: BytesN<32>,
+        threshold: u32,
+    ) -> Result<(), NotaryError> {
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if owner != document.owner {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::validate_threshold(&env, threshold, document.authorized_signers.len())?;
+
+        if let Some(mut latest_version) = Self::unsigned_latest_version(&document)? {
+            latest_version.threshold = threshold;
+            latest_version.updated_at = env.ledger().timestamp();
+            document.versions.set(document.latest_version, latest_version);
+        }
+
+        document.threshold = threshold;
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::ThresholdUpdated(document_hash, threshold));
+
+        Ok(())
+    }
+
+    /// Give signers individual voting weights; versions are then approved once the signed
+    /// weight reaches `required_weight` instead of the signer-count threshold. Unlisted signers
+    /// weigh 1. Pass an empty map and zero to go back to plain M-of-N approval. Like
+    /// `update_signers`, this fails with `InvalidState` while signatures are being collected.
+    pub fn set_signer_weights(
+        env: Env,
+        owner: Address,
+        document_hash: BytesN<32>,
+        weights: Map<Address, u32>,
+        required_weight: u32,
+    ) -> Result<(), NotaryError> {
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if owner != document.owner {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        Self::validate_weights(&env, &document.authorized_signers, &weights, required_weight)?;
+
+        if let Some(mut latest_version) = Self::unsigned_latest_version(&document)? {
+            latest_version.signer_weights = weights.clone();
+            latest_version.required_weight = required_weight;
+            latest_version.updated_at = env.ledger().timestamp();
+            document.versions.set(document.latest_version, latest_version);
+        }
+
+        document.signer_weights = weights;
+        document.required_weight = required_weight;
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::WeightsUpdated(document_hash, required_weight));
+
+        Ok(())
+    }
+
+    /// Push back the expiry of an expiring document by `days`, reviving it if it lapsed
+    pub fn renew_document(
+        env: Env,
+        owner: Address,
+        document_hash: BytesN<32>,
+        days: u64,
+    ) -> Result<u64, NotaryError> {
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if owner != document.owner {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        if days == 0 || days > MAX_EXPIRY_DAYS || document.expires_at == 0 {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        if document.status == DocumentStatus::Revoked {
+            return Err(NotaryError::InvalidStatus);
+        }
+
+        let now = env.ledger().timestamp();
+        document.expires_at = document.expires_at.max(now) + days * SECONDS_PER_DAY;
+        if document.status == DocumentStatus::Expired {
+            document.status = if document.versions.iter().any(|v| v.status == VersionStatus::Approved) {
+                DocumentStatus::Active
+            } else {
+                DocumentStatus::Pending
+            };
+        }
+        document.updated_at = now;
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::DocumentRenewed(document_hash, document.expires_at));
+
+        Ok(document.expires_at)
+    }
+
+    /// Update document status
+    pub fn update_status(
+        env: Env,
+        owner: Address,
+        document_hash: BytesN<32>,
+        new_status: DocumentStatus,
+    ) -> Result<(), NotaryError> {
+        owner.require_auth();
+        Self::require_not_paused(&env)?;
+
+        let (mut document, listing) = Self::load_document_for_update(&env, &document_hash)?;
+
+        if owner != document.owner {
+            return Err(NotaryError::Unauthorized);
+        }
+
+        document.status = new_status.clone();
+        document.updated_at = env.ledger().timestamp();
+
+        Self::save_document(&env, &document, Some(&listing));
+
+        env.events().publish((DOCS,), NotaryEvent::StatusChanged(document_hash, new_status));
+
+        Ok(())
+    }
+
+    /// Extend a document's TTL, along with its versions and list entries, to the network
+    /// maximum; callable (and paid for) by anyone
+    pub fn extend_document_ttl(env: Env, hash: BytesN<32>) -> Result<u32, NotaryError> {
+        let document: Document = env.storage().persistent().get(&DataKey::Document(hash.clone()))
+            .ok_or(NotaryError::NotFound)?;
+
+        let extend_to = env.storage().max_ttl();
+        for key in Self::document_keys(&env, &document).iter() {
+            if env.storage().persistent().has(&key) {
+                env.storage().persistent().extend_ttl(&key, extend_to, extend_to);
+            }
+        }
+        Self::bump_instance(&env);
+
+        env.events().publish((DOCS,), NotaryEvent::TtlExtended(hash, extend_to));
+
+        Ok(extend_to)
+    }
+
+    /// Get notarization fee settings
+    pub fn get_fee_config(env: Env) -> Result<FeeConfig, NotaryError> {
+        env.storage().instance().get(&FEE_CFG)
+            .ok_or(NotaryError::NotFound)
+    }
+
+    /// Update the token fees are paid in and the treasury they are paid to
+    pub fn set_fee_config(
+        env: Env,
+        caller: Address,
+        token: Address,
+        treasury: Address,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::FeeManager)?;
+
+        env.storage().instance().set(&FEE_CFG, &FeeConfig { token: token.clone(), treasury: treasury.clone() });
+        Self::bump_instance(&env);
+
+        env.events().publish((FEES,), NotaryEvent::FeeConfigUpdated(token, treasury));
+
+        Ok(())
+    }
+
+    /// Withdraw fees collected by the contract itself (when it is the treasury) to `to`.
+    /// Takes the token explicitly so balances left over from an earlier fee token can
+    /// still be recovered.
+    pub fn withdraw_fees(
+        env: Env,
+        caller: Address,
+        token: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::FeeManager)?;
+
+        let client = token::Client::new(&env, &token);
+        if amount <= 0 || amount > client.balance(&env.current_contract_address()) {
+            return Err(NotaryError::InvalidInput);
+        }
+
+        client.transfer(&env.current_contract_address(), &to, &amount);
+        env.events().publish((FEES,), NotaryEvent::FeesWithdrawn(token, to, amount));
+
+        Ok(())
+    }
+
+    /// Get a single configuration field by its key
+    pub fn get_config(env: Env, key: Symbol) -> Result<ConfigValue, NotaryError> {
+        let config = Self::load_config(&env);
+
+        if key == MAX_SIGN {
+            Ok(ConfigValue::MaxSigners(config.max_signers))
+        } else if key == MIN_SIGN {
+            Ok(ConfigValue::MinSigners(config.min_signers))
+        } else if key == EXP_DAYS {
+            Ok(ConfigValue::ExpiryDays(config.expiry_days))
+        } else if key == FEE_AMT {
+            Ok(ConfigValue::FeeAmount(config.fee_amount))
+        } else if key == VER_REQ {
+            Ok(ConfigValue::RequiredClaimType(config.required_claim_type))
+        } else if key == RET_DAYS {
+            Ok(ConfigValue::RetentionDays(config.retention_days))
+        } else {
+            Err(NotaryError::NotFound)
+        }
+    }
+
+    /// Get the whole contract configuration
+    pub fn get_full_config(env: Env) -> NotaryConfig {
+        Self::load_config(&env)
+    }
+
+    /// Update a single configuration field
+    pub fn update_config(
+        env: Env,
+        caller: Address,
+        value: ConfigValue,
+    ) -> Result<(), NotaryError> {
+        caller.require_auth();
+
+        Self::require_role(&env, &caller, Role::ConfigManager)?;
+
+        let mut config = Self::load_config(&env);
+        match value {
+            ConfigValue::MaxSigners(max) => {
+                if max < config.min_signers || max > MAX_SIGNERS_LIMIT {
+                    return Err(NotaryError::InvalidInput);
+                }
+                config.max_signers = max;
+            }
+            ConfigValue::MinSigners(min) => {
+                if min == 0 || min > config.max_signers {
+                    return Err(NotaryError::InvalidInput);
+                }
+                config.min_signers = min;
+            }
+            ConfigValue::ExpiryDays(days) => {
+                if days > MAX_EXPIRY_DAYS {
+                    return Err(NotaryError::InvalidInput);
+                }
+                config.expiry_days = days;
+            }
+            ConfigValue::FeeAmount(amount) => {
+                if amount < 0 {
+                    return Err(NotaryError::InvalidInput);
+                }
+                if amount > 0 && !env.storage().instance().has(&FEE_CFG) {
+                    return Err(NotaryError::InvalidState);
+                }
+                config.fee_amount = amount;
+            }
+            ConfigValue::RequiredClaimType(claim_type) => {
+                config.required_claim_type = claim_type;
+            }
+            ConfigValue::RetentionDays(days) => {
+                if days == 0 || days.saturating_mul(DAY_IN_LEDGERS) > env.storage().max_ttl() {
+                    return Err(NotaryError::InvalidInput);
+                }
+                config.retention_days = days;
+            }
         }
 
-        state.settings.set(key, value);
-        env.storage().instance().set(&STATE, &state);
+        env.storage().instance().set(&CONFIG, &config);
+        Self::bump_instance(&env);
 
         Ok(())
     }
@@ -1,264 +1,1627 @@
 #![cfg(test)]
+extern crate std;
+
 use super::*;
+use ed25519_dalek::{Signer, SigningKey};
 use soroban_sdk::{
-    testutils::{Address as _, AuthorizedFunction},
-    vec, map, Vec, Env,
+    symbol_short,
+    testutils::{storage::Persistent as _, Address as _, BytesN as _, Ledger},
+    vec, Env,
 };
 
-mod test {
-    use super::*;
-
-    /// Helper function to setup contract testing environment
-    fn setup() -> (Env, Address, NotaryContractClient<'static>) {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, NotaryContract);
-        let client = NotaryContractClient::new(&env, &contract_id);
-        let admin = Address::random(&env);
-        
-        // Initialize contract
-        client.initialize(admin.clone()).unwrap();
-        
-        (env, admin, client)
-    }
+/// Helper function to setup contract testing environment
+fn setup() -> (Env, Address, NotaryContractClient<'static>) {
+    let env = Env::default();
+    env.mock_all_auths();
 
-    #[test]
-    fn test_initialize() {
-        let env = Env::default();
-        let contract_id = env.register_contract(None, NotaryContract);
-        let client = NotaryContractClient::new(&env, &contract_id);
-        
-        let admin = Address::random(&env);
-        assert!(client.initialize(admin).is_ok());
-    }
+    let contract_id = env.register_contract(None, NotaryContract);
+    let client = NotaryContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    // Initialize contract
+    client.initialize(&admin);
+
+    (env, admin, client)
+}
+
+/// Helper function to register a deterministic ed25519 key for `signer`
+fn register_key(env: &Env, client: &NotaryContractClient, signer: &Address, seed: u8) -> SigningKey {
+    let key = SigningKey::from_bytes(&[seed; 32]);
+    client.register_signing_key(signer, &public_key(env, &key), &0, &u64::MAX);
+    key
+}
+
+/// Helper function to convert a dalek key into the contract's public key type
+fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::from_array(env, &key.verifying_key().to_bytes())
+}
 
-    #[test]
-    fn test_initialize_already_exists() {
-        let (env, admin, client) = setup();
-        
-        // Try to initialize again
-        assert!(client.initialize(admin).is_err());
+/// Helper function to produce a valid signature over a document version
+fn sign(
+    env: &Env,
+    client: &NotaryContractClient,
+    key: &SigningKey,
+    signer: &Address,
+    document_hash: &BytesN<32>,
+    version_index: u32,
+) -> Signature {
+    let timestamp = env.ledger().timestamp();
+    let payload: std::vec::Vec<u8> = client
+        .signing_payload(document_hash, &version_index, &timestamp)
+        .iter()
+        .collect();
+
+    Signature {
+        signer: signer.clone(),
+        timestamp,
+        signature_data: BytesN::from_array(env, &key.sign(&payload).to_bytes()),
+        claim_reference: client.get_claims(signer).get(0)
+            .map(|claim| client.get_claim_id(&claim))
+            .unwrap_or(BytesN::random(env)),
     }
+}
+
+/// Helper function to build a claim signed by `authority` with `key`
+fn make_claim(
+    env: &Env,
+    client: &NotaryContractClient,
+    authority: &Address,
+    key: &SigningKey,
+    user: &Address,
+    claim_type: Symbol,
+    expires_at: u64,
+) -> IdentityClaim {
+    let claim_value = BytesN::random(env);
+    let issued_at = env.ledger().timestamp();
+    let payload: std::vec::Vec<u8> = client
+        .claim_payload(user, &claim_type, &claim_value, &issued_at, &expires_at)
+        .iter()
+        .collect();
 
-    #[test]
-    fn test_document_lifecycle() {
-        let (env, _admin, client) = setup();
-
-        // Create document
-        let hash = BytesN::random(&env);
-        let title = String::from_slice(&env, "Test Document");
-        let signers = vec![&env, Address::random(&env)];
-        let metadata = Map::new(&env);
-        
-        assert!(client.create_document(hash.clone(), title.clone(), signers.clone(), metadata.clone()).is_ok());
-
-        // Test version creation
-        let version_hash = BytesN::random(&env);
-        let version_title = String::from_slice(&env, "Version 2");
-        assert!(client.add_version(hash.clone(), version_hash.clone(), version_title, metadata.clone()).is_ok());
-
-        // Test document signing
-        let signature = Signature {
-            signer: signers.get(0).unwrap(),
-            timestamp: env.ledger().timestamp(),
-            signature_data: BytesN::random(&env),
-            claim_reference: BytesN::random(&env),
-        };
-        assert!(client.sign_document(hash.clone(), signature).is_ok());
-
-        // Verify document
-        let document = client.verify_document(hash).unwrap();
-        assert_eq!(document.status, DocumentStatus::Active);
-        assert_eq!(document.current_version, 1);
+    IdentityClaim {
+        authority: authority.clone(),
+        claim_type,
+        claim_value,
+        signature: BytesN::from_array(env, &key.sign(&payload).to_bytes()),
+        issued_at,
+        expires_at,
+        metadata: Map::new(env),
     }
+}
+
+/// Helper function to have a freshly registered authority issue `user` a claim
+fn issue_claim(
+    env: &Env,
+    client: &NotaryContractClient,
+    admin: &Address,
+    user: &Address,
+    claim_type: Symbol,
+) -> BytesN<32> {
+    let authority = Address::generate(env);
+    client.register_authority(admin, &authority);
+    let key = register_key(env, client, &authority, 100);
 
-    #[test]
-    fn test_authority_management() {
-        let (env, admin, client) = setup();
-
-        // Register authority
-        let authority = Address::random(&env);
-        env.set_source_account(admin);
-        assert!(client.register_authority(authority.clone()).is_ok());
-
-        // Add claim
-        let user = Address::random(&env);
-        let claim = IdentityClaim {
-            authority: authority.clone(),
-            claim_type: symbol_short!("ID"),
-            claim_value: BytesN::random(&env),
-            signature: BytesN::random(&env),
-            issued_at: env.ledger().timestamp(),
-            expires_at: env.ledger().timestamp() + 86400,
-            metadata: Map::new(&env),
-        };
-        
-        env.set_source_account(authority);
-        assert!(client.add_claim(user.clone(), claim).is_ok());
+    let expires_at = env.ledger().timestamp() + 86400;
+    let claim = make_claim(env, client, &authority, &key, user, claim_type, expires_at);
+    client.add_claim(&authority, user, &claim)
+}
+
+/// Helper function to extract the contract error a call was rejected with
+fn contract_error<T, E: core::fmt::Debug>(result: Result<T, Result<NotaryError, E>>) -> NotaryError {
+    match result {
+        Err(Ok(error)) => error,
+        Err(Err(error)) => panic!("call aborted in the host: {:?}", error),
+        Ok(_) => panic!("call succeeded"),
     }
+}
+
+/// Helper function to check a call aborted in the host rather than returning an error
+fn host_abort<T, E>(result: Result<T, Result<NotaryError, E>>) -> bool {
+    matches!(result, Err(Err(_)))
+}
 
-    #[test]
-    fn test_document_status_update() {
-        let (env, _admin, client) = setup();
+#[test]
+fn test_initialize() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, NotaryContract);
+    let client = NotaryContractClient::new(&env, &contract_id);
 
-        // Create document
-        let hash = BytesN::random(&env);
-        let title = String::from_slice(&env, "Test Document");
-        let signers = vec![&env, Address::random(&env)];
-        let metadata = Map::new(&env);
-        
-        client.create_document(hash.clone(), title, signers, metadata).unwrap();
+    let admin = Address::generate(&env);
+    assert!(client.try_initialize(&admin).is_ok());
+}
 
-        // Update status
-        assert!(client.update_status(hash.clone(), DocumentStatus::Revoked).is_ok());
+#[test]
+fn test_initialize_already_exists() {
+    let (_env, admin, client) = setup();
 
-        // Verify status
-        let document = client.verify_document(hash).unwrap();
-        assert_eq!(document.status, DocumentStatus::Revoked);
-    }
+    // Try to initialize again
+    assert_eq!(contract_error(client.try_initialize(&admin)), NotaryError::AlreadyExists);
+}
 
-    #[test]
-    fn test_configuration_management() {
-        let (env, admin, client) = setup();
+#[test]
+fn test_document_lifecycle() {
+    let (env, admin, client) = setup();
 
-        // Update config
-        env.set_source_account(admin);
-        let config_key = MAX_SIGN;
-        let config_value = String::from_slice(&env, "5");
-        assert!(client.update_config(config_key.clone(), config_value.clone()).is_ok());
+    // Create document
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let title = String::from_str(&env, "Test Document");
+    let signers = vec![&env, Address::generate(&env)];
+    let metadata = Map::new(&env);
 
-        // Verify config
-        let result = client.get_config(config_key).unwrap();
-        assert_eq!(result, config_value);
-    }
+    assert!(client.try_create_document(&owner, &hash, &title, &signers, &metadata, &None, &false).is_ok());
+
+    // Test version creation
+    let version_hash = BytesN::random(&env);
+    let version_title = String::from_str(&env, "Version 2");
+    assert!(client.try_add_version(&owner, &hash, &version_hash, &version_title, &metadata, &None).is_ok());
+    assert!(client.try_submit_version(&owner, &hash).is_ok());
+
+    // Test document signing
+    let signer = signers.get(0).unwrap();
+    let key = register_key(&env, &client, &signer, 1);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    let signature = sign(&env, &client, &key, &signer, &hash, 1);
+    assert!(client.try_sign_document(&signer, &hash, &signature).is_ok());
+
+    // Verify document
+    let document = client.verify_document(&hash);
+    assert_eq!(document.status, DocumentStatus::Active);
+    assert_eq!(document.current_version, Some(1));
+    assert_eq!(document.owner, owner);
+    assert_eq!(document.versions.get(1).unwrap().creator, owner);
+}
+
+#[test]
+fn test_caller_auth_required() {
+    let (env, _admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &Map::new(&env),
+        &None,
+        &false,
+    );
+
+    // The owner, not the contract, must have authorized the call
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, owner);
+}
+
+#[test]
+fn test_authority_management() {
+    let (env, admin, client) = setup();
+
+    // Register authority
+    let authority = Address::generate(&env);
+    assert!(client.try_register_authority(&admin, &authority).is_ok());
+    let key = register_key(&env, &client, &authority, 100);
+
+    // Add claim
+    let user = Address::generate(&env);
+    let expires_at = env.ledger().timestamp() + 86400;
+    let claim = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at);
+
+    let claim_id = client.add_claim(&authority, &user, &claim);
+    assert_eq!(client.get_claim_id(&claim), claim_id);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &claim)), NotaryError::AlreadyExists);
+}
+
+#[test]
+fn test_claim_signature_verification() {
+    let (env, admin, client) = setup();
+
+    let authority = Address::generate(&env);
+    client.register_authority(&admin, &authority);
+    let key = register_key(&env, &client, &authority, 100);
+
+    let other = Address::generate(&env);
+    client.register_authority(&admin, &other);
+
+    let user = Address::generate(&env);
+    let expires_at = env.ledger().timestamp() + 86400;
+    let claim = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at);
+
+    // Another authority cannot submit a claim naming someone else as issuer
+    assert_eq!(contract_error(client.try_add_claim(&other, &user, &claim)), NotaryError::InvalidAuthority);
+
+    // The signature is bound to the user it was issued to, so verification aborts
+    let stranger = Address::generate(&env);
+    assert!(host_abort(client.try_add_claim(&authority, &stranger, &claim)));
+
+    // Tampered claim fields invalidate the signature
+    let mut tampered = claim.clone();
+    tampered.claim_type = symbol_short!("KYC");
+    assert!(host_abort(client.try_add_claim(&authority, &user, &tampered)));
+
+    // Signed with a key the authority never registered
+    let rogue = SigningKey::from_bytes(&[7; 32]);
+    let forged = make_claim(&env, &client, &authority, &rogue, &user, symbol_short!("ID"), expires_at);
+    assert!(host_abort(client.try_add_claim(&authority, &user, &forged)));
+
+    // An authority with no current key cannot have signed the claim
+    let keyless = Address::generate(&env);
+    client.register_authority(&admin, &keyless);
+    let unkeyed = make_claim(&env, &client, &keyless, &key, &user, symbol_short!("ID"), expires_at);
+    assert_eq!(contract_error(client.try_add_claim(&keyless, &user, &unkeyed)), NotaryError::InvalidSignature);
+
+    assert!(client.try_add_claim(&authority, &user, &claim).is_ok());
+}
+
+#[test]
+fn test_document_status_update() {
+    let (env, admin, client) = setup();
+
+    // Create document
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let title = String::from_str(&env, "Test Document");
+    let signers = vec![&env, Address::generate(&env)];
+    let metadata = Map::new(&env);
+
+    client.create_document(&owner, &hash, &title, &signers, &metadata, &None, &false);
+
+    // Only the owner may update the status
+    let stranger = Address::generate(&env);
+    assert_eq!(contract_error(client.try_update_status(&stranger, &hash, &DocumentStatus::Revoked)), NotaryError::Unauthorized);
+
+    // Update status
+    assert!(client.try_update_status(&owner, &hash, &DocumentStatus::Revoked).is_ok());
+
+    // Verify status
+    let document = client.verify_document(&hash);
+    assert_eq!(document.status, DocumentStatus::Revoked);
+
+    // Revoked documents take no signatures or new versions
+    let signer = signers.get(0).unwrap();
+    let key = register_key(&env, &client, &signer, 1);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    let signature = sign(&env, &client, &key, &signer, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidStatus);
+    let result = client.try_add_version(&owner, &hash, &BytesN::random(&env), &title, &metadata, &None);
+    assert_eq!(contract_error(result), NotaryError::InvalidStatus);
+    assert_eq!(client.verify_document(&hash).status, DocumentStatus::Revoked);
+}
+
+#[test]
+fn test_configuration_management() {
+    let (env, admin, client) = setup();
+
+    // Update config
+    let config_key = MAX_SIGN;
+    let config_value = ConfigValue::MaxSigners(5);
+    assert!(client.try_update_config(&admin, &config_value).is_ok());
+
+    // Verify config
+    let result = client.get_config(&config_key);
+    assert_eq!(result, config_value);
+    assert_eq!(client.get_full_config().max_signers, 5);
+    assert_eq!(contract_error(client.try_get_config(&symbol_short!("UNKNOWN"))), NotaryError::NotFound);
+
+    // Each field is validated on its own
+    assert_eq!(contract_error(client.try_update_config(&Address::generate(&env), &config_value)), NotaryError::Unauthorized);
+    let result = client.try_update_config(&admin, &ConfigValue::MaxSigners(MAX_SIGNERS_LIMIT + 1));
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_update_config(&admin, &ConfigValue::MinSigners(0))), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_update_config(&admin, &ConfigValue::MinSigners(6))), NotaryError::InvalidInput);
+    let result = client.try_update_config(&admin, &ConfigValue::ExpiryDays(MAX_EXPIRY_DAYS + 1));
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_update_config(&admin, &ConfigValue::FeeAmount(-1))), NotaryError::InvalidInput);
+
+    // Fees cannot be switched on before a fee token is configured
+    assert_eq!(contract_error(client.try_update_config(&admin, &ConfigValue::FeeAmount(100))), NotaryError::InvalidState);
+
+    let claim_type = ConfigValue::RequiredClaimType(Some(symbol_short!("KYC")));
+    client.update_config(&admin, &claim_type);
+    assert_eq!(client.get_config(&VER_REQ), claim_type);
+}
+
+#[test]
+fn test_config_enforced() {
+    let (env, admin, client) = setup();
+    client.update_config(&admin, &ConfigValue::MaxSigners(2));
+    client.update_config(&admin, &ConfigValue::ExpiryDays(1));
+
+    let owner = Address::generate(&env);
+    let title = String::from_str(&env, "Test Document");
+    let metadata = Map::new(&env);
+
+    // Signer count bounds
+    let too_few = Vec::new(&env);
+    let too_many = vec![&env, Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &too_few, &metadata, &None, &false);
+    assert_eq!(contract_error(result), NotaryError::TooFewSigners);
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &too_many, &metadata, &None, &false);
+    assert_eq!(contract_error(result), NotaryError::TooManySigners);
+
+    // Global required claim type
+    let signer = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &title, &vec![&env, signer.clone()], &metadata, &None, &false);
+    let key = register_key(&env, &client, &signer, 1);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    client.update_config(&admin, &ConfigValue::RequiredClaimType(Some(symbol_short!("KYC"))));
+    let signature = sign(&env, &client, &key, &signer, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::MissingIdentityClaim);
+    client.update_config(&admin, &ConfigValue::RequiredClaimType(None));
+
+    // Documents past EXP_DAYS can no longer be signed
+    env.ledger().set_timestamp(SECONDS_PER_DAY);
+    let claim_id = issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    let mut signature = sign(&env, &client, &key, &signer, &hash, 0);
+    signature.claim_reference = claim_id;
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidStatus);
+}
+
+#[test]
+fn test_unauthorized_actions() {
+    let (env, _admin, client) = setup();
+
+    // Try to register authority from non-admin account
+    let unauthorized = Address::generate(&env);
+    let authority = Address::generate(&env);
+    assert_eq!(contract_error(client.try_register_authority(&unauthorized, &authority)), NotaryError::Unauthorized);
+}
+
+#[test]
+fn test_multiple_signatures() {
+    let (env, admin, client) = setup();
+
+    // Create document with multiple signers
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let title = String::from_str(&env, "Multi-Sig Document");
+    let signers = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env)
+    ];
+    let metadata = Map::new(&env);
+
+    client.create_document(&owner, &hash, &title, &signers, &metadata, &Some(3), &false);
+
+    // Add signatures
+    for (i, signer) in signers.iter().enumerate() {
+        let key = register_key(&env, &client, &signer, i as u8 + 1);
+        issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+        let signature = sign(&env, &client, &key, &signer, &hash, 0);
 
-    #[test]
-    #[should_panic(expected = "Unauthorized")]
-    fn test_unauthorized_actions() {
-        let (env, _admin, client) = setup();
-
-        // Try to register authority from non-admin account
-        let unauthorized = Address::random(&env);
-        env.set_source_account(unauthorized);
-        
-        let authority = Address::random(&env);
-        client.register_authority(authority).unwrap();
+        assert!(client.try_sign_document(&signer, &hash, &signature).is_ok());
     }
 
-    #[test]
-    fn test_multiple_signatures() {
-        let (env, _admin, client) = setup();
-
-        // Create document with multiple signers
-        let hash = BytesN::random(&env);
-        let title = String::from_slice(&env, "Multi-Sig Document");
-        let signers = vec![
-            &env,
-            Address::random(&env),
-            Address::random(&env),
-            Address::random(&env)
+    // Verify all signatures are present
+    let document = client.verify_document(&hash);
+    let current_version = document.versions.get(document.current_version.unwrap()).unwrap();
+    assert_eq!(current_version.signatures.len(), signers.len());
+    assert_eq!(document.status, DocumentStatus::Active);
+}
+
+#[test]
+fn test_signature_signer_mismatch() {
+    let (env, _admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signers = vec![&env, Address::generate(&env), Address::generate(&env)];
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &Map::new(&env),
+        &None,
+        &false,
+    );
+
+    // A signer cannot submit a signature recorded under someone else
+    let signature = Signature {
+        signer: signers.get(1).unwrap(),
+        timestamp: env.ledger().timestamp(),
+        signature_data: BytesN::random(&env),
+        claim_reference: BytesN::random(&env),
+    };
+    let caller = signers.get(0).unwrap();
+    assert_eq!(contract_error(client.try_sign_document(&caller, &hash, &signature)), NotaryError::InvalidSignature);
+}
+
+#[test]
+fn test_expired_claims() {
+    let (env, admin, client) = setup();
+
+    // Register authority
+    let authority = Address::generate(&env);
+    client.register_authority(&admin, &authority);
+    let key = register_key(&env, &client, &authority, 100);
+
+    // Add expired claim
+    let user = Address::generate(&env);
+    let expires_at = env.ledger().timestamp(); // Expired
+    let claim = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at);
+
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &claim)), NotaryError::ExpiredClaim);
+}
+
+#[test]
+fn test_user_documents() {
+    let (env, _admin, client) = setup();
+
+    // Create multiple documents
+    let user = Address::generate(&env);
+
+    let doc1_hash = BytesN::random(&env);
+    let doc2_hash = BytesN::random(&env);
+    let title = String::from_str(&env, "Test Document");
+    let signers = vec![&env, user.clone()];
+    let metadata = Map::new(&env);
+
+    // Create documents
+    client.create_document(&user, &doc1_hash, &title, &signers, &metadata, &None, &false);
+    client.create_document(&user, &doc2_hash, &title, &signers, &metadata, &None, &false);
+
+    // Get user documents
+    let page = client.get_user_documents(&user, &0, &10, &None);
+    assert_eq!(page.documents, vec![&env, doc1_hash.clone(), doc2_hash.clone()]);
+    assert_eq!(page.next_offset, None);
+    assert_eq!(client.get_user_document_count(&user), 2);
+
+    // Page through one document at a time
+    let page = client.get_user_documents(&user, &0, &1, &None);
+    assert_eq!(page.documents, vec![&env, doc1_hash.clone()]);
+    assert_eq!(page.next_offset, Some(1));
+    let page = client.get_user_documents(&user, &1, &1, &None);
+    assert_eq!(page.documents, vec![&env, doc2_hash.clone()]);
+    assert_eq!(page.next_offset, None);
+    assert_eq!(client.get_user_documents(&user, &5, &1, &None).documents.len(), 0);
+
+    // Filter by status
+    client.update_status(&user, &doc2_hash, &DocumentStatus::Revoked);
+    let revoked = client.get_user_documents(&user, &0, &10, &Some(DocumentStatus::Revoked));
+    assert_eq!(revoked.documents, vec![&env, doc2_hash]);
+    let pending = client.get_user_documents(&user, &0, &10, &Some(DocumentStatus::Pending));
+    assert_eq!(pending.documents, vec![&env, doc1_hash]);
+
+    // Page sizes are bounded
+    assert_eq!(contract_error(client.try_get_user_documents(&user, &0, &0, &None)), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_get_user_documents(&user, &0, &(MAX_PAGE_SIZE + 1), &None)), NotaryError::InvalidInput);
+    assert_eq!(client.get_user_document_count(&Address::generate(&env)), 0);
+}
+
+#[test]
+fn test_document_not_found() {
+    let (env, _admin, client) = setup();
+
+    let hash = BytesN::random(&env);
+    assert_eq!(contract_error(client.try_verify_document(&hash)), NotaryError::NotFound);
+}
+
+#[test]
+fn test_invalid_authority() {
+    let (env, _admin, client) = setup();
+
+    // Try to add claim without being registered authority
+    let unauthorized = Address::generate(&env);
+
+    let user = Address::generate(&env);
+    let claim = IdentityClaim {
+        authority: unauthorized.clone(),
+        claim_type: symbol_short!("ID"),
+        claim_value: BytesN::random(&env),
+        signature: BytesN::random(&env),
+        issued_at: env.ledger().timestamp(),
+        expires_at: env.ledger().timestamp() + 86400,
+        metadata: Map::new(&env),
+    };
+
+    assert_eq!(contract_error(client.try_add_claim(&unauthorized, &user, &claim)), NotaryError::InvalidAuthority);
+}
+
+#[test]
+fn test_per_key_storage() {
+    let (env, _admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &Map::new(&env),
+        &None,
+        &false,
+    );
+
+    // Each document and index lives in its own persistent entry
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        assert!(storage.has(&DataKey::Document(hash.clone())));
+        assert!(storage.has(&DataKey::IndexLen(DocIndex::Owner(owner.clone()))));
+        assert!(storage.has(&DataKey::IndexItem(DocIndex::Owner(owner.clone()), 0)));
+        assert!(!storage.has(&DataKey::Document(BytesN::random(&env))));
+    });
+}
+
+#[test]
+fn test_document_ttl_bumped() {
+    let (env, admin, client) = setup();
+
+    // Shorten the retention period so the bump is observable
+    let result = client.try_update_config(&Address::generate(&env), &ConfigValue::RetentionDays(30));
+    assert_eq!(contract_error(result), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_update_config(&admin, &ConfigValue::RetentionDays(0))), NotaryError::InvalidInput);
+    client.update_config(&admin, &ConfigValue::RetentionDays(30));
+    assert_eq!(client.get_config(&RET_DAYS), ConfigValue::RetentionDays(30));
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &Map::new(&env),
+        &None,
+        &false,
+    );
+
+    env.as_contract(&client.address, || {
+        let ttl = env.storage().persistent().get_ttl(&DataKey::Document(hash.clone()));
+        assert_eq!(ttl, 30 * DAY_IN_LEDGERS);
+    });
+
+    // Give the document a rejected version and a draft so it has entries of every kind
+    let signer = signers.get(0).unwrap();
+    client.reject_version(&hash, &0, &signer, &BytesN::random(&env));
+    let draft = BytesN::random(&env);
+    client.add_version(&owner, &hash, &draft, &String::from_str(&env, "Draft"), &Map::new(&env), &None);
+
+    // Anyone can extend a notarization to the network maximum, including the entries
+    // its version lookups and document lists depend on
+    let extended = client.extend_document_ttl(&hash);
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        assert_eq!(extended, env.storage().max_ttl());
+        let keys = [
+            DataKey::Document(hash.clone()),
+            DataKey::Version(hash.clone()),
+            DataKey::Version(draft.clone()),
+            DataKey::Rejection(hash.clone(), 0),
+            DataKey::IndexLen(DocIndex::Owner(owner.clone())),
+            DataKey::IndexItem(DocIndex::Owner(owner.clone()), 0),
+            DataKey::IndexPos(DocIndex::Owner(owner.clone()), hash.clone()),
+            DataKey::IndexPos(DocIndex::Signer(signer.clone()), hash.clone()),
+            DataKey::IndexPos(DocIndex::Status(DocumentStatus::Pending), hash.clone()),
         ];
-        let metadata = Map::new(&env);
-        
-        client.create_document(hash.clone(), title, signers.clone(), metadata).unwrap();
-
-        // Add signatures
-        for i in 0..signers.len() {
-            let signer = signers.get(i).unwrap();
-            let signature = Signature {
-                signer: signer.clone(),
-                timestamp: env.ledger().timestamp(),
-                signature_data: BytesN::random(&env),
-                claim_reference: BytesN::random(&env),
-            };
-            
-            env.set_source_account(signer);
-            assert!(client.sign_document(hash.clone(), signature).is_ok());
+        for key in keys.iter() {
+            assert_eq!(storage.get_ttl(key), extended);
         }
+    });
+
+    assert_eq!(contract_error(client.try_extend_document_ttl(&BytesN::random(&env))), NotaryError::NotFound);
+}
+
+#[test]
+fn test_signature_verification() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signer = Address::generate(&env);
+    let signers = vec![&env, signer.clone()];
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &Map::new(&env),
+        &None,
+        &false,
+    );
+
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+
+    // No registered key
+    let unregistered = SigningKey::from_bytes(&[9; 32]);
+    let signature = sign(&env, &client, &unregistered, &signer, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidSignature);
+
+    // Signatures that fail ed25519 verification abort in the host: one made with a
+    // key other than the registered one, and one over the wrong version index
+    register_key(&env, &client, &signer, 1);
+    assert!(host_abort(client.try_sign_document(&signer, &hash, &signature)));
+    let key = SigningKey::from_bytes(&[1; 32]);
+    let signature = sign(&env, &client, &key, &signer, &hash, 1);
+    assert!(host_abort(client.try_sign_document(&signer, &hash, &signature)));
+
+    // Timestamp in the future
+    let mut signature = sign(&env, &client, &key, &signer, &hash, 0);
+    signature.timestamp += 1;
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidSignature);
+
+    let signature = sign(&env, &client, &key, &signer, &hash, 0);
+    assert!(client.try_sign_document(&signer, &hash, &signature).is_ok());
+    assert_eq!(client.verify_document(&hash).status, DocumentStatus::Active);
+}
+
+#[test]
+fn test_signing_key_registry() {
+    let (env, _admin, client) = setup();
+    env.ledger().set_timestamp(1_000);
+
+    let signer = Address::generate(&env);
+    let first = SigningKey::from_bytes(&[1; 32]);
+    let second = SigningKey::from_bytes(&[2; 32]);
+
+    // Empty or inverted validity windows are rejected
+    let result = client.try_register_signing_key(&signer, &public_key(&env, &first), &500, &500);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+
+    client.register_signing_key(&signer, &public_key(&env, &first), &500, &u64::MAX);
+    let result = client.try_register_signing_key(&signer, &public_key(&env, &first), &0, &u64::MAX);
+    assert_eq!(contract_error(result), NotaryError::AlreadyExists);
+    assert_eq!(client.get_signing_key(&signer, &1_000), public_key(&env, &first));
+    assert_eq!(contract_error(client.try_get_signing_key(&signer, &499)), NotaryError::NotFound);
+
+    // Rotation closes the old key's window at the current timestamp
+    client.rotate_signing_key(&signer, &public_key(&env, &first), &public_key(&env, &second), &u64::MAX);
+    assert_eq!(client.get_signing_key(&signer, &999), public_key(&env, &first));
+    assert_eq!(client.get_signing_key(&signer, &1_000), public_key(&env, &second));
+    assert_eq!(client.get_signing_keys(&signer).len(), 2);
+
+    // Revoked keys never resolve
+    client.revoke_signing_key(&signer, &public_key(&env, &first));
+    assert_eq!(contract_error(client.try_get_signing_key(&signer, &999)), NotaryError::NotFound);
+    assert_eq!(contract_error(client.try_revoke_signing_key(&signer, &public_key(&env, &first))), NotaryError::NotFound);
+    assert_eq!(client.get_signing_keys(&signer).get(0).unwrap().revoked_at, 1_000);
+}
+
+#[test]
+fn test_signing_key_lifecycle() {
+    let (env, admin, client) = setup();
+    env.ledger().set_timestamp(1_000);
+
+    let owner = Address::generate(&env);
+    let signer = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Lease"), &vec![&env, signer.clone()], &Map::new(&env), &None, &false);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+
+    let old = register_key(&env, &client, &signer, 1);
+    let new = SigningKey::from_bytes(&[2; 32]);
+    let early = sign(&env, &client, &old, &signer, &hash, 0);
+    client.rotate_signing_key(&signer, &public_key(&env, &old), &public_key(&env, &new), &u64::MAX);
 
-        // Verify all signatures are present
-        let document = client.verify_document(hash).unwrap();
-        let current_version = document.versions.get(document.current_version as u32).unwrap();
-        assert_eq!(current_version.signatures.len(), signers.len());
-        assert_eq!(document.status, DocumentStatus::Active);
+    // A signature dated before the rotation is too old once the rotation is behind us
+    env.ledger().set_timestamp(5_000);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &early)), NotaryError::InvalidSignature);
+
+    // Backdating a fresh signature with the retired key does not help either
+    let mut backdated = sign(&env, &client, &old, &signer, &hash, 0);
+    backdated.timestamp = 500;
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &backdated)), NotaryError::InvalidSignature);
+
+    // A current signature is checked against the key valid now, so the old key fails
+    assert!(host_abort(client.try_sign_document(&signer, &hash, &sign(&env, &client, &old, &signer, &hash, 0))));
+    client.sign_document(&signer, &hash, &sign(&env, &client, &new, &signer, &hash, 0));
+
+    // Retired keys stay on record for lookups at past timestamps
+    assert_eq!(client.get_signing_key(&signer, &500), public_key(&env, &old));
+    assert_eq!(client.get_signing_key(&signer, &5_000), public_key(&env, &new));
+
+    // The key cap counts only live keys, and rotation swaps one live key for another
+    for seed in 3..12 {
+        register_key(&env, &client, &signer, seed);
     }
+    let spare = public_key(&env, &SigningKey::from_bytes(&[20; 32]));
+    let result = client.try_register_signing_key(&signer, &spare, &0, &u64::MAX);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+    client.rotate_signing_key(&signer, &public_key(&env, &new), &spare, &u64::MAX);
+    let keys = client.get_signing_keys(&signer);
+    assert_eq!(keys.len(), MAX_SIGNING_KEYS + 2);
+    assert_eq!(keys.iter().filter(|k| k.revoked_at == 0 && k.valid_until > 5_000).count() as u32, MAX_SIGNING_KEYS);
+
+    // Revoked keys stay on record and can never be registered again
+    client.revoke_signing_key(&signer, &spare);
+    let revoked = client.get_signing_keys(&signer).iter().find(|k| k.public_key == spare).unwrap();
+    assert_eq!(revoked.revoked_at, 5_000);
+    let result = client.try_register_signing_key(&signer, &spare, &0, &u64::MAX);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+    let result = client.try_rotate_signing_key(&signer, &public_key(&env, &old), &spare, &u64::MAX);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+
+    // Revoking made room for another key
+    register_key(&env, &client, &signer, 21);
+
+    // Key lists are kept alive for the retention period
+    env.as_contract(&client.address, || {
+        let ttl = env.storage().persistent().get_ttl(&DataKey::SignerKeys(signer.clone()));
+        assert_eq!(ttl, DEFAULT_RETENTION_DAYS * DAY_IN_LEDGERS);
+    });
+}
+
+#[test]
+fn test_identity_claim_required() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    let signer = Address::generate(&env);
+    let signers = vec![&env, signer.clone()];
+    let mut metadata = Map::new(&env);
+    metadata.set(VER_REQ, String::from_str(&env, "KYC"));
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &metadata,
+        &None,
+        &false,
+    );
+    let key = register_key(&env, &client, &signer, 1);
+
+    // No claim referenced
+    let signature = sign(&env, &client, &key, &signer, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::MissingIdentityClaim);
+
+    // Claim of the wrong type for this document
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    let signature = sign(&env, &client, &key, &signer, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::MissingIdentityClaim);
+
+    // Expired claim
+    let kyc_claim = issue_claim(&env, &client, &admin, &signer, symbol_short!("KYC"));
+    let mut signature = sign(&env, &client, &key, &signer, &hash, 0);
+    signature.claim_reference = kyc_claim.clone();
+    env.ledger().set_timestamp(env.ledger().timestamp() + 86400);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::ExpiredClaim);
+
+    // Fresh claim of the required type
+    let kyc_claim = issue_claim(&env, &client, &admin, &signer, symbol_short!("KYC"));
+    let mut signature = sign(&env, &client, &key, &signer, &hash, 0);
+    signature.claim_reference = kyc_claim.clone();
+    assert!(client.try_sign_document(&signer, &hash, &signature).is_ok());
+
+    // Malformed requirements are refused at creation
+    let mut bad_metadata = Map::new(&env);
+    bad_metadata.set(VER_REQ, String::from_str(&env, "not a type"));
+    let result = client.try_create_document(
+        &owner,
+        &BytesN::random(&env),
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &bad_metadata,
+        &None,
+        &false,
+    );
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+
+    // The global requirement still applies to documents with their own
+    let hash = BytesN::random(&env);
+    client.create_document(
+        &owner,
+        &hash,
+        &String::from_str(&env, "Test Document"),
+        &signers,
+        &metadata,
+        &None,
+        &false,
+    );
+    client.update_config(&admin, &ConfigValue::RequiredClaimType(Some(symbol_short!("ID"))));
+    let mut signature = sign(&env, &client, &key, &signer, &hash, 0);
+    signature.claim_reference = kyc_claim.clone();
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::MissingIdentityClaim);
+
+    // Holding a live claim of each required type is enough
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    assert!(client.try_sign_document(&signer, &hash, &signature).is_ok());
+}
+
+#[test]
+fn test_notarization_fees() {
+    let (env, admin, client) = setup();
 
-    #[test]
-    fn test_expired_claims() {
-        let (env, admin, client) = setup();
-
-        // Register authority
-        let authority = Address::random(&env);
-        env.set_source_account(admin);
-        client.register_authority(authority.clone()).unwrap();
-
-        // Add expired claim
-        let user = Address::random(&env);
-        let claim = IdentityClaim {
-            authority: authority.clone(),
-            claim_type: symbol_short!("ID"),
-            claim_value: BytesN::random(&env),
-            signature: BytesN::random(&env),
-            issued_at: env.ledger().timestamp(),
-            expires_at: env.ledger().timestamp() - 1, // Expired
-            metadata: Map::new(&env),
-        };
-        
-        env.set_source_account(authority);
-        assert!(client.add_claim(user, claim).is_err());
+    let token_admin = Address::generate(&env);
+    let token_id = env.register_stellar_asset_contract_v2(token_admin).address();
+    let token = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+
+    // Fees accumulate in the contract itself so they can be withdrawn
+    client.set_fee_config(&admin, &token_id, &client.address);
+    client.update_config(&admin, &ConfigValue::FeeAmount(100));
+
+    let owner = Address::generate(&env);
+    token_admin_client.mint(&owner, &1_000);
+
+    let hash = BytesN::random(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    let metadata = Map::new(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Test Document"), &signers, &metadata, &None, &false);
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Version 2"), &metadata, &None);
+    assert_eq!(token.balance(&owner), 800);
+    assert_eq!(token.balance(&client.address), 200);
+
+    // Registered authorities notarize for free
+    let authority = Address::generate(&env);
+    client.register_authority(&admin, &authority);
+    client.create_document(&authority, &BytesN::random(&env), &String::from_str(&env, "Attestation"), &signers, &metadata, &None, &false);
+    assert_eq!(token.balance(&client.address), 200);
+
+    // Withdrawals are admin-only and bounded by the collected balance
+    let treasury = Address::generate(&env);
+    assert_eq!(contract_error(client.try_withdraw_fees(&owner, &token_id, &treasury, &100)), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_withdraw_fees(&admin, &token_id, &treasury, &201)), NotaryError::InvalidInput);
+    client.withdraw_fees(&admin, &token_id, &treasury, &150);
+    assert_eq!(token.balance(&treasury), 150);
+    assert_eq!(token.balance(&client.address), 50);
+
+    // Switching the fee token does not strand the old balance
+    let new_token_id = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.set_fee_config(&admin, &new_token_id, &client.address);
+    assert_eq!(contract_error(client.try_withdraw_fees(&admin, &new_token_id, &treasury, &50)), NotaryError::InvalidInput);
+    client.withdraw_fees(&admin, &token_id, &treasury, &50);
+    assert_eq!(token.balance(&treasury), 200);
+    assert_eq!(token.balance(&client.address), 0);
+}
+
+#[test]
+fn test_signer_list_validation() {
+    let (env, admin, client) = setup();
+    client.update_config(&admin, &ConfigValue::MaxSigners(3));
+
+    let owner = Address::generate(&env);
+    let title = String::from_str(&env, "Test Document");
+    let metadata = Map::new(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    // Duplicate signers are rejected
+    let duplicated = vec![&env, alice.clone(), bob.clone(), alice.clone()];
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &duplicated, &metadata, &None, &false);
+    assert_eq!(contract_error(result), NotaryError::DuplicateSigner);
+
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &title, &vec![&env, alice.clone()], &metadata, &None, &false);
+
+    // Signer-list changes are bounded the same way and restricted to the owner
+    let carol = Address::generate(&env);
+    let dave = Address::generate(&env);
+    let too_many = vec![&env, alice.clone(), bob.clone(), carol.clone(), dave.clone()];
+    assert_eq!(contract_error(client.try_update_signers(&owner, &hash, &too_many)), NotaryError::TooManySigners);
+    assert_eq!(contract_error(client.try_update_signers(&owner, &hash, &Vec::new(&env))), NotaryError::TooFewSigners);
+    assert_eq!(contract_error(client.try_update_signers(&owner, &hash, &duplicated)), NotaryError::DuplicateSigner);
+    assert_eq!(contract_error(client.try_update_signers(&alice, &hash, &vec![&env, bob.clone()])), NotaryError::Unauthorized);
+
+    // An unsigned version picks up the new list
+    client.update_signers(&owner, &hash, &vec![&env, alice.clone(), bob.clone()]);
+    client.set_threshold(&owner, &hash, &2);
+    let document = client.verify_document(&hash);
+    assert_eq!(document.authorized_signers.len(), 2);
+    assert_eq!(document.versions.get(0).unwrap().required_signers.len(), 2);
+
+    // Once signing started the list is frozen until the version is approved
+    let key = register_key(&env, &client, &alice, 1);
+    issue_claim(&env, &client, &admin, &alice, symbol_short!("ID"));
+    client.sign_document(&alice, &hash, &sign(&env, &client, &key, &alice, &hash, 0));
+    let result = client.try_update_signers(&owner, &hash, &vec![&env, alice.clone(), carol.clone()]);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+}
+
+#[test]
+fn test_document_expiry() {
+    let (env, admin, client) = setup();
+    client.update_config(&admin, &ConfigValue::ExpiryDays(30));
+
+    let owner = Address::generate(&env);
+    let title = String::from_str(&env, "Test Document");
+    let signers = vec![&env, Address::generate(&env)];
+
+    // Global EXP_DAYS applies by default
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &title, &signers, &Map::new(&env), &None, &false);
+    assert_eq!(client.verify_document(&hash).expires_at, 30 * SECONDS_PER_DAY);
+
+    // Per-document override through metadata, with "0" meaning no expiry
+    let mut metadata = Map::new(&env);
+    metadata.set(EXP_DAYS, String::from_str(&env, "0"));
+    let forever = BytesN::random(&env);
+    client.create_document(&owner, &forever, &title, &signers, &metadata, &None, &false);
+    assert_eq!(client.verify_document(&forever).expires_at, 0);
+
+    metadata.set(EXP_DAYS, String::from_str(&env, "ten"));
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &signers, &metadata, &None, &false);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+
+    // Views report the document as expired once the deadline passes
+    env.ledger().set_timestamp(30 * SECONDS_PER_DAY);
+    assert_eq!(client.verify_document(&hash).status, DocumentStatus::Expired);
+    assert_eq!(client.verify_document(&forever).status, DocumentStatus::Pending);
+
+    // Only the owner may renew, which revives the document
+    assert_eq!(contract_error(client.try_renew_document(&Address::generate(&env), &hash, &10)), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_renew_document(&owner, &hash, &0)), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_renew_document(&owner, &forever, &10)), NotaryError::InvalidInput);
+    let expires_at = client.renew_document(&owner, &hash, &10);
+    assert_eq!(expires_at, 40 * SECONDS_PER_DAY);
+
+    let document = client.verify_document(&hash);
+    assert_eq!(document.status, DocumentStatus::Pending);
+    assert_eq!(document.expires_at, expires_at);
+}
+
+#[test]
+fn test_threshold_approval() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let title = String::from_str(&env, "Board Resolution");
+    let metadata = Map::new(&env);
+    let signers = vec![&env, Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+
+    // Threshold must lie within 1..=signers.len()
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &signers, &metadata, &Some(0), &false);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+    let result = client.try_create_document(&owner, &BytesN::random(&env), &title, &signers, &metadata, &Some(4), &false);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+
+    // 2 of 3 directors
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &title, &signers, &metadata, &Some(2), &false);
+    assert_eq!(client.verify_document(&hash).versions.get(0).unwrap().threshold, 2);
+
+    for (i, signer) in signers.iter().take(2).enumerate() {
+        assert_eq!(client.verify_document(&hash).status, DocumentStatus::Pending);
+        let key = register_key(&env, &client, &signer, i as u8 + 1);
+        issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+        client.sign_document(&signer, &hash, &sign(&env, &client, &key, &signer, &hash, 0));
     }
 
-    #[test]
-    fn test_user_documents() {
-        let (env, _admin, client) = setup();
-
-        // Create multiple documents
-        let user = Address::random(&env);
-        env.set_source_account(user.clone());
-
-        let doc1_hash = BytesN::random(&env);
-        let doc2_hash = BytesN::random(&env);
-        let title = String::from_slice(&env, "Test Document");
-        let signers = vec![&env, user.clone()];
-        let metadata = Map::new(&env);
-
-        // Create documents
-        client.create_document(doc1_hash.clone(), title.clone(), signers.clone(), metadata.clone()).unwrap();
-        client.create_document(doc2_hash.clone(), title, signers, metadata).unwrap();
-
-        // Get user documents
-        let user_docs = client.get_user_documents(user).unwrap();
-        assert_eq!(user_docs.len(), 2);
-        assert!(user_docs.contains(&doc1_hash));
-        assert!(user_docs.contains(&doc2_hash));
+    let document = client.verify_document(&hash);
+    assert_eq!(document.status, DocumentStatus::Active);
+    assert_eq!(document.versions.get(0).unwrap().status, VersionStatus::Approved);
+
+    // Threshold changes are validated against the signer list
+    assert_eq!(contract_error(client.try_set_threshold(&owner, &hash, &4)), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_set_threshold(&signers.get(0).unwrap(), &hash, &1)), NotaryError::Unauthorized);
+    client.set_threshold(&owner, &hash, &3);
+    let result = client.try_update_signers(&owner, &hash, &vec![&env, signers.get(0).unwrap()]);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+
+    // Defaults to unanimous approval
+    let unanimous = BytesN::random(&env);
+    client.create_document(&owner, &unanimous, &title, &signers, &metadata, &None, &false);
+    assert_eq!(client.verify_document(&unanimous).threshold, 3);
+}
+
+#[test]
+fn test_weighted_approval() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let majority = Address::generate(&env);
+    let minority_a = Address::generate(&env);
+    let minority_b = Address::generate(&env);
+    let signers = vec![&env, majority.clone(), minority_a.clone(), minority_b.clone()];
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Shareholder Vote"), &signers, &Map::new(&env), &None, &false);
+
+    // Weights must name listed signers and make the required weight reachable
+    let mut weights = Map::new(&env);
+    weights.set(Address::generate(&env), 5);
+    assert_eq!(contract_error(client.try_set_signer_weights(&owner, &hash, &weights, &3)), NotaryError::InvalidInput);
+
+    let mut weights = Map::new(&env);
+    weights.set(majority.clone(), 5);
+    assert_eq!(contract_error(client.try_set_signer_weights(&owner, &hash, &weights, &8)), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_set_signer_weights(&owner, &hash, &weights, &0)), NotaryError::InvalidInput);
+    assert_eq!(contract_error(client.try_set_signer_weights(&majority, &hash, &weights, &6)), NotaryError::Unauthorized);
+    client.set_signer_weights(&owner, &hash, &weights, &6);
+
+    let sign_as = |signer: &Address, seed: u8| {
+        let key = register_key(&env, &client, signer, seed);
+        issue_claim(&env, &client, &admin, signer, symbol_short!("ID"));
+        client.sign_document(signer, &hash, &sign(&env, &client, &key, signer, &hash, 0));
+    };
+
+    // The two minority signers together weigh 2 of the required 6
+    sign_as(&minority_a, 1);
+    sign_as(&minority_b, 2);
+    assert_eq!(client.verify_document(&hash).status, DocumentStatus::Pending);
+
+    sign_as(&majority, 3);
+    let document = client.verify_document(&hash);
+    assert_eq!(document.status, DocumentStatus::Active);
+    assert_eq!(document.versions.get(0).unwrap().required_weight, 6);
+
+    // New versions inherit the weighting
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Amendment"), &Map::new(&env), &None);
+    let version = client.verify_document(&hash).versions.get(1).unwrap();
+    assert_eq!(version.signer_weights.get(majority.clone()), Some(5));
+    assert_eq!(version.required_weight, 6);
+
+    // Signers carrying a weight cannot be dropped from the list
+    let result = client.try_update_signers(&owner, &hash, &vec![&env, minority_a, minority_b]);
+    assert_eq!(contract_error(result), NotaryError::InvalidInput);
+}
+
+#[test]
+fn test_ordered_signing() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let employee = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let notary = Address::generate(&env);
+    let signers = vec![&env, employee.clone(), manager.clone(), notary.clone()];
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Employment Contract"), &signers, &Map::new(&env), &None, &true);
+
+    let mut keys = std::vec::Vec::new();
+    for (i, signer) in signers.iter().enumerate() {
+        keys.push(register_key(&env, &client, &signer, i as u8 + 1));
+        issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
     }
 
-    #[test]
-    fn test_document_not_found() {
-        let (env, _admin, client) = setup();
+    // The manager cannot sign before the employee
+    assert_eq!(client.get_next_signer(&hash), Some(employee.clone()));
+    let early = sign(&env, &client, &keys[1], &manager, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&manager, &hash, &early)), NotaryError::InvalidState);
+
+    client.sign_document(&employee, &hash, &sign(&env, &client, &keys[0], &employee, &hash, 0));
+    assert_eq!(client.get_next_signer(&hash), Some(manager.clone()));
+
+    // Nor can the notary skip ahead of the manager
+    let early = sign(&env, &client, &keys[2], &notary, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&notary, &hash, &early)), NotaryError::InvalidState);
+
+    client.sign_document(&manager, &hash, &sign(&env, &client, &keys[1], &manager, &hash, 0));
+    client.sign_document(&notary, &hash, &sign(&env, &client, &keys[2], &notary, &hash, 0));
+    assert_eq!(client.get_next_signer(&hash), None);
+    assert_eq!(client.verify_document(&hash).status, DocumentStatus::Active);
+
+    // Unordered documents have no next signer
+    let unordered = BytesN::random(&env);
+    client.create_document(&owner, &unordered, &String::from_str(&env, "Memo"), &signers, &Map::new(&env), &None, &false);
+    assert_eq!(contract_error(client.try_get_next_signer(&unordered)), NotaryError::InvalidState);
+
+    // Rejected versions and drafts are not waiting on anyone
+    let rejected = BytesN::random(&env);
+    client.create_document(&owner, &rejected, &String::from_str(&env, "Offer"), &signers, &Map::new(&env), &None, &true);
+    client.reject_version(&rejected, &0, &employee, &BytesN::random(&env));
+    assert_eq!(client.get_next_signer(&rejected), None);
+    let draft = String::from_str(&env, "Offer v2");
+    client.add_version(&owner, &rejected, &BytesN::random(&env), &draft, &Map::new(&env), &None);
+    assert_eq!(client.get_next_signer(&rejected), None);
+}
+
+#[test]
+fn test_version_rejection() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let signers = vec![&env, alice.clone(), bob.clone()];
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Lease"), &signers, &Map::new(&env), &None, &false);
+
+    let alice_key = register_key(&env, &client, &alice, 1);
+    issue_claim(&env, &client, &admin, &alice, symbol_short!("ID"));
+    let bob_key = register_key(&env, &client, &bob, 2);
+    issue_claim(&env, &client, &admin, &bob, symbol_short!("ID"));
+    client.sign_document(&alice, &hash, &sign(&env, &client, &alice_key, &alice, &hash, 0));
+
+    let reason = BytesN::random(&env);
+
+    // Only required signers who have not signed may reject, and only the current version
+    assert_eq!(contract_error(client.try_reject_version(&hash, &0, &owner, &reason)), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_reject_version(&hash, &0, &alice, &reason)), NotaryError::InvalidState);
+    assert_eq!(contract_error(client.try_reject_version(&hash, &1, &bob, &reason)), NotaryError::InvalidVersion);
+
+    client.reject_version(&hash, &0, &bob, &reason);
+
+    let version = client.verify_document(&hash).versions.get(0).unwrap();
+    assert_eq!(version.status, VersionStatus::Rejected);
+    let rejection = client.get_rejection(&hash, &0);
+    assert_eq!(rejection.signer, bob);
+    assert_eq!(rejection.reason_hash, reason);
+
+    // A rejected version accepts no further signatures or rejections
+    let signature = sign(&env, &client, &bob_key, &bob, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&bob, &hash, &signature)), NotaryError::InvalidStatus);
+    assert_eq!(contract_error(client.try_reject_version(&hash, &0, &bob, &reason)), NotaryError::InvalidStatus);
+
+    // Versions of paused or revoked documents cannot be rejected either
+    let pending = BytesN::random(&env);
+    client.create_document(&owner, &pending, &String::from_str(&env, "Sublease"), &signers, &Map::new(&env), &None, &false);
+    client.pause(&admin);
+    assert_eq!(contract_error(client.try_reject_version(&pending, &0, &bob, &reason)), NotaryError::InvalidState);
+    client.unpause(&admin);
+    client.update_status(&owner, &pending, &DocumentStatus::Revoked);
+    assert_eq!(contract_error(client.try_reject_version(&pending, &0, &bob, &reason)), NotaryError::InvalidStatus);
+    assert_eq!(client.verify_document(&pending).versions.get(0).unwrap().status, VersionStatus::PendingApproval);
+}
+
+#[test]
+fn test_version_superseded() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let signer = Address::generate(&env);
+    let signers = vec![&env, signer.clone()];
+    let hash = BytesN::random(&env);
+    let metadata = Map::new(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Policy"), &signers, &metadata, &None, &false);
+
+    assert_eq!(client.verify_document(&hash).current_version, None);
+
+    let key = register_key(&env, &client, &signer, 1);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    client.sign_document(&signer, &hash, &sign(&env, &client, &key, &signer, &hash, 0));
+
+    // A new draft does not replace the approved version until it is approved itself
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Policy v2"), &metadata, &None);
+    let document = client.verify_document(&hash);
+    assert_eq!(document.current_version, Some(0));
+    assert_eq!(document.latest_version, 1);
+    assert_eq!(document.versions.get(0).unwrap().status, VersionStatus::Approved);
+
+    client.submit_version(&owner, &hash);
+    client.sign_document(&signer, &hash, &sign(&env, &client, &key, &signer, &hash, 1));
+    let document = client.verify_document(&hash);
+    assert_eq!(document.current_version, Some(1));
+    assert_eq!(document.versions.get(0).unwrap().status, VersionStatus::Superseded);
+    assert_eq!(document.versions.get(1).unwrap().status, VersionStatus::Approved);
+
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Policy v3"), &metadata, &None);
+    let document = client.verify_document(&hash);
+    assert_eq!(document.current_version, Some(1));
+    assert_eq!(document.latest_version, 2);
+}
+
+#[test]
+fn test_version_submission() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let signer = Address::generate(&env);
+    let signers = vec![&env, signer.clone()];
+    let hash = BytesN::random(&env);
+    let metadata = Map::new(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Charter"), &signers, &metadata, &None, &false);
+
+    let key = register_key(&env, &client, &signer, 1);
+    issue_claim(&env, &client, &admin, &signer, symbol_short!("ID"));
+    client.sign_document(&signer, &hash, &sign(&env, &client, &key, &signer, &hash, 0));
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Draft"), &metadata, &None);
+
+    // Drafts cannot be signed or rejected, but stay editable
+    let signature = sign(&env, &client, &key, &signer, &hash, 1);
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidStatus);
+    assert_eq!(contract_error(client.try_reject_version(&hash, &1, &signer, &BytesN::random(&env))), NotaryError::InvalidStatus);
+    let result = client.try_update_draft(&signer, &hash, &String::from_str(&env, "Edit"), &metadata);
+    assert_eq!(contract_error(result), NotaryError::Unauthorized);
+
+    let mut edited = Map::new(&env);
+    edited.set(symbol_short!("NOTE"), String::from_str(&env, "final"));
+    client.update_draft(&owner, &hash, &String::from_str(&env, "Charter v2"), &edited);
+
+    assert_eq!(contract_error(client.try_submit_version(&signer, &hash)), NotaryError::Unauthorized);
+    client.submit_version(&owner, &hash);
+
+    // Submitted versions are frozen
+    let version = client.verify_document(&hash).versions.get(1).unwrap();
+    assert_eq!(version.status, VersionStatus::PendingApproval);
+    assert_eq!(version.title, String::from_str(&env, "Charter v2"));
+    assert_eq!(version.metadata, edited);
+    let result = client.try_update_draft(&owner, &hash, &String::from_str(&env, "Late"), &metadata);
+    assert_eq!(contract_error(result), NotaryError::InvalidStatus);
+    assert_eq!(contract_error(client.try_submit_version(&owner, &hash)), NotaryError::InvalidStatus);
 
-        let hash = BytesN::random(&env);
-        assert!(client.verify_document(hash).is_err());
+    client.sign_document(&signer, &hash, &signature);
+    assert_eq!(client.verify_document(&hash).versions.get(1).unwrap().status, VersionStatus::Approved);
+
+    // Approved versions take no further signatures
+    assert_eq!(contract_error(client.try_sign_document(&signer, &hash, &signature)), NotaryError::InvalidStatus);
+
+    // Drafts of revoked documents can be neither edited nor submitted
+    client.add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Charter v3"), &metadata, &None);
+    client.update_status(&owner, &hash, &DocumentStatus::Revoked);
+    let result = client.try_update_draft(&owner, &hash, &String::from_str(&env, "Edit"), &metadata);
+    assert_eq!(contract_error(result), NotaryError::InvalidStatus);
+    assert_eq!(contract_error(client.try_submit_version(&owner, &hash)), NotaryError::InvalidStatus);
+    assert_eq!(client.verify_document(&hash).versions.get(2).unwrap().status, VersionStatus::Draft);
+}
+
+#[test]
+fn test_version_chain() {
+    let (env, _admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    let hash = BytesN::random(&env);
+    let metadata = Map::new(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Spec"), &signers, &metadata, &None, &false);
+
+    let v1 = BytesN::random(&env);
+    let v2 = BytesN::random(&env);
+    let branch = BytesN::random(&env);
+    client.add_version(&owner, &hash, &v1, &String::from_str(&env, "Spec v1"), &metadata, &None);
+    client.add_version(&owner, &hash, &v2, &String::from_str(&env, "Spec v2"), &metadata, &None);
+    client.add_version(&owner, &hash, &branch, &String::from_str(&env, "Spec alt"), &metadata, &Some(1));
+
+    // Each version points at the one before it unless branched explicitly
+    let document = client.verify_document(&hash);
+    assert_eq!(document.versions.get(1).unwrap().parent_hash, hash);
+    assert_eq!(document.versions.get(2).unwrap().parent_hash, v1);
+    assert_eq!(document.versions.get(3).unwrap().parent_hash, v1);
+
+    assert_eq!(client.get_version_chain(&hash, &2), vec![&env, v2, v1.clone(), hash.clone()]);
+    assert_eq!(client.get_version_chain(&hash, &3), vec![&env, branch, v1, hash.clone()]);
+    assert_eq!(client.get_version_chain(&hash, &0), vec![&env, hash.clone()]);
+
+    assert_eq!(contract_error(client.try_get_version_chain(&hash, &4)), NotaryError::InvalidVersion);
+    let orphan = client.try_add_version(&owner, &hash, &BytesN::random(&env), &String::from_str(&env, "Orphan"), &metadata, &Some(9));
+    assert_eq!(contract_error(orphan), NotaryError::InvalidVersion);
+}
+
+#[test]
+fn test_version_lookup() {
+    let (env, _admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let signers = vec![&env, Address::generate(&env)];
+    let hash = BytesN::random(&env);
+    let metadata = Map::new(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Deed"), &signers, &metadata, &None, &false);
+
+    let revision = BytesN::random(&env);
+    client.add_version(&owner, &hash, &revision, &String::from_str(&env, "Deed v2"), &metadata, &None);
+
+    let (location, version) = client.verify_version(&revision);
+    assert_eq!(location, VersionRef { document_hash: hash.clone(), version_index: 1 });
+    assert_eq!(version.title, String::from_str(&env, "Deed v2"));
+
+    let (location, _) = client.verify_version(&hash);
+    assert_eq!(location.version_index, 0);
+
+    assert_eq!(contract_error(client.try_verify_version(&BytesN::random(&env))), NotaryError::NotFound);
+
+    // A hash is registered once, whether as a document or as a version
+    let result = client.try_add_version(&owner, &hash, &revision, &String::from_str(&env, "Again"), &metadata, &None);
+    assert_eq!(contract_error(result), NotaryError::AlreadyExists);
+    let result = client.try_add_version(&owner, &hash, &hash, &String::from_str(&env, "Root"), &metadata, &None);
+    assert_eq!(contract_error(result), NotaryError::AlreadyExists);
+    let duplicate = client.try_create_document(&owner, &revision, &String::from_str(&env, "Copy"), &signers, &metadata, &None, &false);
+    assert_eq!(contract_error(duplicate), NotaryError::AlreadyExists);
+}
+
+#[test]
+fn test_document_search() {
+    let (env, admin, client) = setup();
+
+    let owner = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let signers = vec![&env, alice.clone(), bob.clone()];
+    let metadata = Map::new(&env);
+    let open = BytesN::random(&env);
+    let ordered = BytesN::random(&env);
+    client.create_document(&owner, &open, &String::from_str(&env, "Open"), &signers, &metadata, &None, &false);
+    client.create_document(&owner, &ordered, &String::from_str(&env, "Ordered"), &signers, &metadata, &None, &true);
+
+    let page = |docs: DocumentPage| docs.documents;
+    let pending = Some(DocumentStatus::Pending);
+    assert_eq!(page(client.get_signer_documents(&bob, &0, &10)), vec![&env, open.clone(), ordered.clone()]);
+    assert_eq!(page(client.get_awaiting_signature(&alice, &0, &10)), vec![&env, open.clone(), ordered.clone()]);
+    // Ordered documents wait only on the next signer
+    assert_eq!(page(client.get_awaiting_signature(&bob, &0, &10)), vec![&env, open.clone()]);
+    assert_eq!(page(client.get_documents_by_status(&DocumentStatus::Pending, &0, &10)).len(), 2);
+
+    let alice_key = register_key(&env, &client, &alice, 1);
+    issue_claim(&env, &client, &admin, &alice, symbol_short!("ID"));
+    let bob_key = register_key(&env, &client, &bob, 2);
+    issue_claim(&env, &client, &admin, &bob, symbol_short!("ID"));
+
+    client.sign_document(&alice, &open, &sign(&env, &client, &alice_key, &alice, &open, 0));
+    client.sign_document(&alice, &ordered, &sign(&env, &client, &alice_key, &alice, &ordered, 0));
+    assert_eq!(client.get_awaiting_signature(&alice, &0, &10).documents.len(), 0);
+    assert_eq!(client.get_awaiting_signature(&bob, &0, &10).documents.len(), 2);
+
+    client.sign_document(&bob, &open, &sign(&env, &client, &bob_key, &bob, &open, 0));
+    assert_eq!(page(client.get_awaiting_signature(&bob, &0, &10)), vec![&env, ordered.clone()]);
+    assert_eq!(page(client.get_documents_by_status(&DocumentStatus::Active, &0, &10)), vec![&env, open.clone()]);
+    assert_eq!(page(client.get_user_documents(&owner, &0, &10, &pending)), vec![&env, ordered.clone()]);
+
+    // Revoked documents drop out of the awaiting list
+    client.update_status(&owner, &ordered, &DocumentStatus::Revoked);
+    assert_eq!(client.get_awaiting_signature(&bob, &0, &10).documents.len(), 0);
+    assert_eq!(page(client.get_documents_by_status(&DocumentStatus::Revoked, &0, &10)), vec![&env, ordered]);
+    assert_eq!(client.get_documents_by_status(&DocumentStatus::Pending, &0, &10).documents.len(), 0);
+
+    // Signer lists follow update_signers
+    let carol = Address::generate(&env);
+    client.update_signers(&owner, &open, &vec![&env, alice.clone(), carol.clone()]);
+    assert_eq!(page(client.get_signer_documents(&bob, &0, &10)).len(), 1);
+    assert_eq!(page(client.get_signer_documents(&carol, &0, &10)), vec![&env, open]);
+    assert_eq!(contract_error(client.try_get_signer_documents(&bob, &0, &0)), NotaryError::InvalidInput);
+
+    // Removals between page reads neither skip nor repeat the remaining documents
+    let batch = [BytesN::random(&env), BytesN::random(&env), BytesN::random(&env)];
+    for hash in batch.iter() {
+        client.create_document(&owner, hash, &String::from_str(&env, "Batch"), &signers, &metadata, &None, &false);
     }
+    let first = client.get_documents_by_status(&DocumentStatus::Pending, &0, &1);
+    assert_eq!(first.documents, vec![&env, batch[0].clone()]);
+    client.update_status(&owner, &batch[0], &DocumentStatus::Revoked);
+    let mut seen = std::vec::Vec::new();
+    let mut offset = first.next_offset;
+    while let Some(next) = offset {
+        let page = client.get_documents_by_status(&DocumentStatus::Pending, &next, &1);
+        seen.extend(page.documents.iter());
+        offset = page.next_offset;
+    }
+    assert_eq!(seen, std::vec![batch[1].clone(), batch[2].clone()]);
+
+    // Emptied slots at the end of a list are trimmed
+    client.update_status(&owner, &batch[2], &DocumentStatus::Revoked);
+    client.update_status(&owner, &batch[1], &DocumentStatus::Revoked);
+    assert_eq!(client.get_documents_by_status(&DocumentStatus::Pending, &0, &1).next_offset, None);
+}
+
+#[test]
+fn test_admin_transfer() {
+    let (env, admin, client) = setup();
+
+    let new_admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    // Nothing to accept or cancel without an offer
+    assert_eq!(contract_error(client.try_accept_admin()), NotaryError::NotFound);
+    assert_eq!(contract_error(client.try_cancel_admin_proposal(&admin)), NotaryError::NotFound);
+    assert_eq!(contract_error(client.try_propose_admin(&stranger, &new_admin)), NotaryError::Unauthorized);
+
+    // An offer can be withdrawn
+    client.propose_admin(&admin, &stranger);
+    assert_eq!(contract_error(client.try_cancel_admin_proposal(&stranger)), NotaryError::Unauthorized);
+    client.cancel_admin_proposal(&admin);
+    assert_eq!(client.get_pending_admin(), None);
+    assert_eq!(contract_error(client.try_accept_admin()), NotaryError::NotFound);
+
+    // The role only moves once the new admin accepts
+    client.propose_admin(&admin, &new_admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    assert_eq!(client.get_admin_address(), admin);
+
+    client.accept_admin();
+    assert_eq!(
+        env.auths().first().map(|(address, _)| address.clone()),
+        Some(new_admin.clone())
+    );
+    assert_eq!(client.get_admin_address(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+
+    // Admin-only entrypoints follow the new admin
+    let authority = Address::generate(&env);
+    assert_eq!(contract_error(client.try_register_authority(&admin, &authority)), NotaryError::Unauthorized);
+    client.register_authority(&new_admin, &authority);
+}
 
-    #[test]
-    fn test_invalid_authority() {
-        let (env, _admin, client) = setup();
-
-        // Try to add claim without being registered authority
-        let unauthorized = Address::random(&env);
-        env.set_source_account(unauthorized.clone());
-
-        let user = Address::random(&env);
-        let claim = IdentityClaim {
-            authority: unauthorized,
-            claim_type: symbol_short!("ID"),
-            claim_value: BytesN::random(&env),
-            signature: BytesN::random(&env),
-            issued_at: env.ledger().timestamp(),
-            expires_at: env.ledger().timestamp() + 86400,
-            metadata: Map::new(&env),
-        };
-
-        assert!(client.add_claim(user, claim).is_err());
+#[test]
+fn test_roles() {
+    let (env, admin, client) = setup();
+
+    let operator = Address::generate(&env);
+    let authority = Address::generate(&env);
+
+    // Without a role, privileged entrypoints are closed
+    assert!(!client.has_role(&Role::AuthorityManager, &operator));
+    assert_eq!(contract_error(client.try_register_authority(&operator, &authority)), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_grant_role(&operator, &Role::AuthorityManager, &operator)), NotaryError::Unauthorized);
+
+    client.grant_role(&admin, &Role::AuthorityManager, &operator);
+    assert!(client.has_role(&Role::AuthorityManager, &operator));
+    assert!(client.has_role(&Role::AuthorityManager, &admin));
+    client.register_authority(&operator, &authority);
+
+    // Roles are scoped to their own entrypoints
+    assert_eq!(contract_error(client.try_update_config(&operator, &ConfigValue::MaxSigners(10))), NotaryError::Unauthorized);
+    client.grant_role(&admin, &Role::ConfigManager, &operator);
+    client.update_config(&operator, &ConfigValue::MaxSigners(10));
+
+    // Roles and authority records in use are kept alive for the retention period
+    env.ledger().with_mut(|li| li.sequence_number += 2 * DAY_IN_LEDGERS);
+    client.update_config(&operator, &ConfigValue::MaxSigners(10));
+    client.get_authority(&authority);
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        let retention = DEFAULT_RETENTION_DAYS * DAY_IN_LEDGERS;
+        assert_eq!(storage.get_ttl(&DataKey::Role(Role::ConfigManager, operator.clone())), retention);
+        assert_eq!(storage.get_ttl(&DataKey::Authority(authority.clone())), retention);
+    });
+
+    client.revoke_role(&admin, &Role::AuthorityManager, &operator);
+    assert_eq!(contract_error(client.try_register_authority(&operator, &Address::generate(&env))), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_revoke_role(&admin, &Role::AuthorityManager, &operator)), NotaryError::NotFound);
+
+    // Pausing stops document and claim changes until resumed
+    let owner = Address::generate(&env);
+    let existing = BytesN::random(&env);
+    let title = String::from_str(&env, "Paused");
+    let signers = vec![&env, Address::generate(&env)];
+    client.create_document(&owner, &existing, &title, &signers, &Map::new(&env), &None, &false);
+    let claim_id = issue_claim(&env, &client, &admin, &owner, symbol_short!("ID"));
+
+    assert_eq!(contract_error(client.try_pause(&operator)), NotaryError::Unauthorized);
+    client.grant_role(&admin, &Role::Pauser, &operator);
+    client.pause(&operator);
+
+    let hash = BytesN::random(&env);
+    let result = client.try_create_document(&owner, &hash, &title, &signers, &Map::new(&env), &None, &false);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+    let result = client.try_update_status(&owner, &existing, &DocumentStatus::Revoked);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+    let result = client.try_update_signers(&owner, &existing, &vec![&env, Address::generate(&env)]);
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+    assert_eq!(contract_error(client.try_revoke_claim(&admin, &claim_id, &symbol_short!("FRAUD"))), NotaryError::InvalidState);
+    assert_eq!(client.verify_document(&existing).status, DocumentStatus::Pending);
+
+    client.unpause(&operator);
+    client.create_document(&owner, &hash, &title, &signers, &Map::new(&env), &None, &false);
+
+    // Upgrades need the upgrader role
+    assert_eq!(contract_error(client.try_upgrade(&operator, &BytesN::random(&env))), NotaryError::Unauthorized);
+}
+
+#[test]
+fn test_authority_suspension_and_scope() {
+    let (env, admin, client) = setup();
+
+    let authority = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.register_authority(&admin, &authority);
+    let key = register_key(&env, &client, &authority, 100);
+    let expires_at = env.ledger().timestamp() + 86400;
+
+    // A KYC provider scoped to ID claims cannot attest anything else
+    client.set_authority_scope(&admin, &authority, &vec![&env, symbol_short!("ID")]);
+    let email = make_claim(&env, &client, &authority, &key, &user, symbol_short!("EMAIL"), expires_at);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &email)), NotaryError::Unauthorized);
+    let id = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at);
+    let claim_id = client.add_claim(&authority, &user, &id);
+
+    // Suspended authorities cannot issue claims and their claims do not count when signing
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Form"), &vec![&env, user.clone()], &Map::new(&env), &None, &false);
+    let user_key = register_key(&env, &client, &user, 1);
+    let mut signature = sign(&env, &client, &user_key, &user, &hash, 0);
+    signature.claim_reference = claim_id.clone();
+
+    client.suspend_authority(&admin, &authority, &symbol_short!("COMPROM"));
+    assert_eq!(client.get_authority(&authority).suspended_reason, Some(symbol_short!("COMPROM")));
+    let result = client.try_suspend_authority(&admin, &authority, &symbol_short!("COMPROM"));
+    assert_eq!(contract_error(result), NotaryError::InvalidState);
+    let later = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at + 1);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &later)), NotaryError::InvalidAuthority);
+    assert_eq!(contract_error(client.try_sign_document(&user, &hash, &signature)), NotaryError::InvalidAuthority);
+
+    client.reinstate_authority(&admin, &authority);
+    client.sign_document(&user, &hash, &signature);
+
+    // Only the authority manager can remove an authority
+    assert_eq!(contract_error(client.try_remove_authority(&user, &authority)), NotaryError::Unauthorized);
+    client.remove_authority(&admin, &authority);
+    assert_eq!(contract_error(client.try_get_authority(&authority)), NotaryError::NotFound);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &later)), NotaryError::InvalidAuthority);
+    assert_eq!(contract_error(client.try_remove_authority(&admin, &authority)), NotaryError::NotFound);
+
+    // Registering it again does not revive claims issued before then
+    env.ledger().with_mut(|li| li.timestamp += 60);
+    client.register_authority(&admin, &authority);
+    assert_eq!(client.get_authority(&authority).registered_at, env.ledger().timestamp());
+    assert!(!client.is_claim_valid(&signature.claim_reference));
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &later)), NotaryError::InvalidAuthority);
+
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "Form"), &vec![&env, user.clone()], &Map::new(&env), &None, &false);
+    let mut signature = sign(&env, &client, &user_key, &user, &hash, 0);
+    signature.claim_reference = claim_id;
+    assert_eq!(contract_error(client.try_sign_document(&user, &hash, &signature)), NotaryError::InvalidAuthority);
+
+    // Claims it issues afterwards count again
+    let fresh = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at + 60);
+    signature.claim_reference = client.add_claim(&authority, &user, &fresh);
+    client.sign_document(&user, &hash, &signature);
+}
+
+#[test]
+fn test_claim_list_bounded() {
+    let (env, admin, client) = setup();
+
+    let authority = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.register_authority(&admin, &authority);
+    let key = register_key(&env, &client, &authority, 100);
+    let expires_at = env.ledger().timestamp() + 86400;
+
+    // Each user's claim list holds a bounded number of claims
+    let mut claim_ids = std::vec::Vec::new();
+    for _ in 0..MAX_CLAIMS_PER_USER {
+        let claim = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at);
+        claim_ids.push(client.add_claim(&authority, &user, &claim));
     }
+    let extra = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at + 86400);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &extra)), NotaryError::InvalidInput);
+
+    // Revoked claims make room, but stay resolvable by id
+    client.revoke_claim(&authority, &claim_ids[0], &symbol_short!("ERROR"));
+    client.add_claim(&authority, &user, &extra);
+    assert_eq!(client.get_claims(&user).len(), MAX_CLAIMS_PER_USER);
+    assert_eq!(client.get_claim_revocation(&claim_ids[0]).revoked_by, authority);
+    assert!(!client.is_claim_valid(&claim_ids[0]));
+
+    // So do expired ones
+    env.ledger().set_timestamp(expires_at);
+    let fresh = make_claim(&env, &client, &authority, &key, &user, symbol_short!("ID"), expires_at + 86400);
+    let fresh_id = client.add_claim(&authority, &user, &fresh);
+    let claims = client.get_claims(&user);
+    assert_eq!(claims.len(), 2);
+    assert_eq!(client.get_claim_id(&claims.get(1).unwrap()), fresh_id);
+    assert_eq!(contract_error(client.try_add_claim(&authority, &user, &fresh)), NotaryError::AlreadyExists);
+}
+
+#[test]
+fn test_claim_revocation() {
+    let (env, admin, client) = setup();
+
+    let user = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let hash = BytesN::random(&env);
+    client.create_document(&owner, &hash, &String::from_str(&env, "KYC"), &vec![&env, user.clone()], &Map::new(&env), &None, &false);
+
+    let claim_id = issue_claim(&env, &client, &admin, &user, symbol_short!("ID"));
+    let authority = client.get_claims(&user).get(0).unwrap().authority;
+    assert!(client.is_claim_valid(&claim_id));
+    assert!(!client.is_claim_valid(&BytesN::random(&env)));
+
+    // Only the issuer or an authority manager may revoke
+    let reason = symbol_short!("FRAUD");
+    assert_eq!(contract_error(client.try_revoke_claim(&user, &claim_id, &reason)), NotaryError::Unauthorized);
+    assert_eq!(contract_error(client.try_revoke_claim(&authority, &BytesN::random(&env), &reason)), NotaryError::NotFound);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.revoke_claim(&authority, &claim_id, &reason);
+    let revocation = client.get_claim_revocation(&claim_id);
+    assert_eq!(revocation.revoked_by, authority);
+    assert_eq!(revocation.reason, reason);
+    assert_eq!(revocation.revoked_at, 1_000);
+    assert!(!client.is_claim_valid(&claim_id));
+    assert_eq!(contract_error(client.try_revoke_claim(&admin, &claim_id, &reason)), NotaryError::AlreadyExists);
+
+    // Revoked claims no longer back signatures
+    let key = register_key(&env, &client, &user, 1);
+    let signature = sign(&env, &client, &key, &user, &hash, 0);
+    assert_eq!(contract_error(client.try_sign_document(&user, &hash, &signature)), NotaryError::RevokedClaim);
+
+    // The admin can revoke claims from any authority
+    let second = issue_claim(&env, &client, &admin, &user, symbol_short!("ID"));
+    client.revoke_claim(&admin, &second, &symbol_short!("ERROR"));
+    assert!(!client.is_claim_valid(&second));
+
+    // Claim lists are kept alive for the retention period
+    env.as_contract(&client.address, || {
+        let ttl = env.storage().persistent().get_ttl(&DataKey::Claims(user.clone()));
+        assert_eq!(ttl, DEFAULT_RETENTION_DAYS * DAY_IN_LEDGERS);
+    });
 }
@@ -1,13 +1,42 @@
 use soroban_sdk::{
-    contracttype, symbol_short, Address, BytesN, Symbol, Vec, Map, String,
-    xdr::{ScErrorType, ScErrorCode},
+    contracterror, contracttype, symbol_short, Address, BytesN, Symbol, Vec, Map, String,
 };
 
 /// Storage identifiers (max 9 chars)
 pub const ADMIN: Symbol = symbol_short!("ADMIN");
-pub const STATE: Symbol = symbol_short!("STATE");
+pub const PEND_ADM: Symbol = symbol_short!("PEND_ADM");
 pub const DOCS: Symbol = symbol_short!("DOCS");
 pub const AUTH: Symbol = symbol_short!("AUTH");
+pub const KEYS: Symbol = symbol_short!("KEYS");
+pub const FEES: Symbol = symbol_short!("FEES");
+pub const CONFIG: Symbol = symbol_short!("CONFIG");
+pub const FEE_CFG: Symbol = symbol_short!("FEE_CFG");
+pub const ROLES: Symbol = symbol_short!("ROLES");
+pub const PAUSED: Symbol = symbol_short!("PAUSED");
+pub const OPS: Symbol = symbol_short!("OPS");
+
+/// Storage TTL parameters (in ledgers, ~5s each)
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const DEFAULT_RETENTION_DAYS: u32 = 365;
+
+/// Maximum number of live signing keys per address; expired and revoked keys do not count
+pub const MAX_SIGNING_KEYS: u32 = 10;
+
+/// Maximum number of claims listed per user; expired and revoked ones are dropped to make room
+pub const MAX_CLAIMS_PER_USER: u32 = 20;
+
+/// How long after its timestamp a document signature may be submitted, in seconds
+pub const SIGNATURE_MAX_AGE: u64 = 300;
+
+/// Largest page returned by list queries
+pub const MAX_PAGE_SIZE: u32 = 20;
+
+/// Configuration defaults and hard limits
+pub const DEFAULT_MAX_SIGNERS: u32 = 20;
+pub const DEFAULT_MIN_SIGNERS: u32 = 1;
+pub const MAX_SIGNERS_LIMIT: u32 = 50;
+pub const MAX_EXPIRY_DAYS: u64 = 36500;
+pub const SECONDS_PER_DAY: u64 = 86400;
 
 /// Configuration keys (max 9 chars)
 pub const MAX_SIGN: Symbol = symbol_short!("MAX_SIGN");
@@ -15,9 +44,11 @@ pub const MIN_SIGN: Symbol = symbol_short!("MIN_SIGN");
 pub const EXP_DAYS: Symbol = symbol_short!("EXP_DAYS");
 pub const FEE_AMT: Symbol = symbol_short!("FEE_AMT");
 pub const VER_REQ: Symbol = symbol_short!("VER_REQ");
+pub const RET_DAYS: Symbol = symbol_short!("RET_DAYS");
 
 /// Error codes for the contract
-#[derive(Copy, Clone, Debug)]
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum NotaryError {
     AlreadyExists = 1,
@@ -32,24 +63,10 @@ pub enum NotaryError {
     InvalidInput = 10,
     InvalidState = 11,
     OperationFailed = 12,
-}
-
-impl From<soroban_sdk::Error> for NotaryError {
-    fn from(_error: soroban_sdk::Error) -> Self {
-        NotaryError::OperationFailed
-    }
-}
-
-impl From<NotaryError> for soroban_sdk::Error {
-    fn from(error: NotaryError) -> Self {
-        let (type_, code) = match error {
-            NotaryError::AlreadyExists => (ScErrorType::Contract, ScErrorCode::ExistingValue),
-            NotaryError::NotFound => (ScErrorType::Contract, ScErrorCode::MissingValue),
-            NotaryError::Unauthorized => (ScErrorType::Contract, ScErrorCode::InternalError),
-            _ => (ScErrorType::Contract, ScErrorCode::InternalError),
-        };
-        soroban_sdk::Error::from_type_and_code(type_, code)
-    }
+    RevokedClaim = 13,
+    TooFewSigners = 14,
+    TooManySigners = 15,
+    DuplicateSigner = 16,
 }
 
 /// Document status
@@ -73,6 +90,17 @@ pub enum VersionStatus {
     Superseded,
 }
 
+/// Privileges the admin can hand out. The admin itself holds every role.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    ConfigManager,
+    AuthorityManager,
+    FeeManager,
+    Pauser,
+    Upgrader,
+}
+
 /// Identity claim structure
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -86,6 +114,69 @@ pub struct IdentityClaim {
     pub metadata: Map<Symbol, String>,
 }
 
+/// A registered certification authority. An empty `claim_types` list lets it issue
+/// claims of any type. Only claims issued since `registered_at` are honoured, so
+/// removing and re-registering an authority does not revive its earlier claims.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AuthorityRecord {
+    pub claim_types: Vec<Symbol>,
+    pub suspended_reason: Option<Symbol>,
+    pub registered_at: u64,
+    pub updated_at: u64,
+}
+
+/// Who revoked an identity claim, when, and why
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ClaimRevocation {
+    pub revoked_by: Address,
+    pub reason: Symbol,
+    pub revoked_at: u64,
+}
+
+/// Contract configuration
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct NotaryConfig {
+    pub max_signers: u32,
+    pub min_signers: u32,
+    pub expiry_days: u64,
+    pub fee_amount: i128,
+    pub required_claim_type: Option<Symbol>,
+    pub retention_days: u32,
+}
+
+/// A single configuration field with its value, one variant per config key
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ConfigValue {
+    MaxSigners(u32),
+    MinSigners(u32),
+    ExpiryDays(u64),
+    FeeAmount(i128),
+    RequiredClaimType(Option<Symbol>),
+    RetentionDays(u32),
+}
+
+/// Notarization fee destination
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeConfig {
+    pub token: Address,
+    pub treasury: Address,
+}
+
+/// Signing public key bound to an address
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SignerKey {
+    pub public_key: BytesN<32>,
+    pub valid_from: u64,
+    pub valid_until: u64,
+    pub revoked_at: u64,
+}
+
 /// Signature structure
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -96,6 +187,23 @@ pub struct Signature {
     pub claim_reference: BytesN<32>,
 }
 
+/// Why and by whom a version was rejected
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Rejection {
+    pub signer: Address,
+    pub reason_hash: BytesN<32>,
+    pub rejected_at: u64,
+}
+
+/// Where a version hash lives: its document and position in the version list
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VersionRef {
+    pub document_hash: BytesN<32>,
+    pub version_index: u32,
+}
+
 /// Document version structure with fixed parent hash handling
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -109,10 +217,15 @@ pub struct DocumentVersion {
     pub updated_at: u64,
     pub signatures: Vec<Signature>,
     pub required_signers: Vec<Address>,
+    pub threshold: u32,
+    pub signer_weights: Map<Address, u32>,
+    pub required_weight: u32,
+    pub ordered: bool,
     pub metadata: Map<Symbol, String>,
 }
 
-/// Document structure
+/// Document structure. `current_version` is the approved version in force, `None`
+/// until a version has been approved.
 #[derive(Clone, Debug)]
 #[contracttype]
 pub struct Document {
@@ -121,22 +234,53 @@ pub struct Document {
     pub owner: Address,
     pub created_at: u64,
     pub updated_at: u64,
-    pub current_version: u32,
+    pub current_version: Option<u32>,
+    pub latest_version: u32,
+    pub expires_at: u64,
     pub versions: Vec<DocumentVersion>,
     pub authorized_signers: Vec<Address>,
+    pub threshold: u32,
+    pub signer_weights: Map<Address, u32>,
+    pub required_weight: u32,
+    pub ordered: bool,
     pub metadata: Map<Symbol, String>,
 }
 
-/// Contract storage structure
+/// Document lists that can be paged through, stored one entry per item
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum DocIndex {
+    Owner(Address),
+    Signer(Address),
+    Awaiting(Address),
+    Status(DocumentStatus),
+}
+
+/// One page of a document list; `next_offset` is `None` once the list is exhausted
 #[derive(Clone, Debug)]
 #[contracttype]
-pub struct NotaryState {
-    pub admin: Address,
-    pub documents: Map<BytesN<32>, Document>,
-    pub user_documents: Map<Address, Vec<BytesN<32>>>,
-    pub authorities: Vec<Address>,
-    pub claims: Map<Address, Vec<IdentityClaim>>,
-    pub settings: Map<Symbol, String>,
+pub struct DocumentPage {
+    pub documents: Vec<BytesN<32>>,
+    pub next_offset: Option<u32>,
+}
+
+/// Persistent storage keys, one ledger entry per key
+#[derive(Clone, Debug)]
+#[contracttype]
+pub enum DataKey {
+    Document(BytesN<32>),
+    IndexLen(DocIndex),
+    IndexItem(DocIndex, u32),
+    IndexPos(DocIndex, BytesN<32>),
+    Authority(Address),
+    Claim(BytesN<32>),
+    Claims(Address),
+    ClaimHolder(BytesN<32>),
+    ClaimRevocation(BytesN<32>),
+    SignerKeys(Address),
+    Rejection(BytesN<32>, u32),
+    Version(BytesN<32>),
+    Role(Role, Address),
 }
 
 /// Event types for logging
@@ -146,18 +290,33 @@ pub enum NotaryEvent {
     VersionAdded(BytesN<32>),
     DocumentSigned(BytesN<32>),
     StatusChanged(BytesN<32>, DocumentStatus),
+    SignersUpdated(BytesN<32>),
+    DocumentRenewed(BytesN<32>, u64),
+    ThresholdUpdated(BytesN<32>, u32),
+    WeightsUpdated(BytesN<32>, u32),
+    VersionSubmitted(BytesN<32>, u32),
+    VersionRejected(BytesN<32>, u32, Address),
     ClaimAdded(Address),
+    ClaimRevoked(Address, BytesN<32>, Symbol),
     AuthorityAdded(Address),
-}
-
-impl From<&NotaryError> for soroban_sdk::Error {
-    fn from(error: &NotaryError) -> Self {
-        match error {
-            NotaryError::AlreadyExists => soroban_sdk::Error::from_type_and_code(ScErrorType::Contract, ScErrorCode::ExistingValue),
-            NotaryError::NotFound => soroban_sdk::Error::from_type_and_code(ScErrorType::Contract, ScErrorCode::MissingValue),
-            NotaryError::Unauthorized => soroban_sdk::Error::from_type_and_code(ScErrorType::Contract, ScErrorCode::InternalError),
-            _ => soroban_sdk::Error::from_type_and_code(ScErrorType::Contract, ScErrorCode::InternalError),
-        }
-    }
+    AuthorityRemoved(Address),
+    AuthoritySuspended(Address, Symbol),
+    AuthorityReinstated(Address),
+    AuthorityScopeUpdated(Address, Vec<Symbol>),
+    TtlExtended(BytesN<32>, u32),
+    KeyRegistered(Address, BytesN<32>),
+    KeyRotated(Address, BytesN<32>),
+    KeyRevoked(Address, BytesN<32>),
+    FeeConfigUpdated(Address, Address),
+    FeeCharged(Address, i128),
+    FeesWithdrawn(Address, Address, i128),
+    AdminProposed(Address, Address),
+    AdminProposalCancelled(Address),
+    AdminChanged(Address, Address),
+    RoleGranted(Role, Address),
+    RoleRevoked(Role, Address),
+    Paused(Address),
+    Unpaused(Address),
+    Upgraded(BytesN<32>),
 }
 